@@ -0,0 +1,41 @@
+use imgui::Io;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Decides whether gamepad inputs go to imgui or are left to the game.
+///
+/// Disabled by default: imgui receives every input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InputArbitration {
+    pub enabled: bool,
+    /// imgui gets the inputs while `Io::want_capture_keyboard` is set
+    pub want_capture_keyboard: bool,
+    /// imgui gets the inputs while `Io::nav_active` is set
+    pub nav_active: bool,
+    /// Button that switches UI mode on and off, imgui gets every input in UI mode.
+    ///
+    /// Its presses never reach imgui nor the game.
+    pub ui_mode_toggle: Option<gilrs::Button>,
+}
+
+impl Default for InputArbitration {
+    fn default() -> Self {
+        InputArbitration {
+            enabled: false,
+            want_capture_keyboard: true,
+            nav_active: true,
+            ui_mode_toggle: None,
+        }
+    }
+}
+
+impl InputArbitration {
+    pub(crate) fn ui_wants_input(&self, io: &Io, ui_mode: bool) -> bool {
+        !self.enabled
+            || ui_mode
+            || (self.want_capture_keyboard && io.want_capture_keyboard)
+            || (self.nav_active && io.nav_active)
+    }
+}
@@ -0,0 +1,65 @@
+use gilrs::PowerInfo;
+use imgui::{ImColor32, Ui};
+
+const BODY_SIZE: [f32; 2] = [24.0, 12.0];
+const TIP_SIZE: [f32; 2] = [3.0, 6.0];
+/// Charge below which the battery is drawn in red
+const LOW_CHARGE: u8 = 20;
+
+const OUTLINE_COLOR: ImColor32 = ImColor32::from_rgb(160, 160, 160);
+const CHARGE_COLOR: ImColor32 = ImColor32::from_rgb(80, 200, 120);
+const LOW_CHARGE_COLOR: ImColor32 = ImColor32::from_rgb(200, 80, 80);
+
+/// Draws a battery icon followed by the power state, e.g. "Charging 40%"
+pub fn battery_indicator(ui: &Ui, power: PowerInfo) {
+    let charge = match power {
+        PowerInfo::Discharging(charge) | PowerInfo::Charging(charge) => Some(charge.min(100)),
+        PowerInfo::Charged => Some(100),
+        _ => None,
+    };
+
+    let [x, y] = ui.cursor_screen_pos();
+    let y = y + (ui.text_line_height() - BODY_SIZE[1]) / 2.0;
+    let draw_list = ui.get_window_draw_list();
+    let body_end = [x + BODY_SIZE[0], y + BODY_SIZE[1]];
+    draw_list.add_rect([x, y], body_end, OUTLINE_COLOR).build();
+    let tip_y = y + (BODY_SIZE[1] - TIP_SIZE[1]) / 2.0;
+    draw_list
+        .add_rect(
+            [body_end[0], tip_y],
+            [body_end[0] + TIP_SIZE[0], tip_y + TIP_SIZE[1]],
+            OUTLINE_COLOR,
+        )
+        .filled(true)
+        .build();
+    if let Some(charge) = charge {
+        let color = if charge < LOW_CHARGE {
+            LOW_CHARGE_COLOR
+        } else {
+            CHARGE_COLOR
+        };
+        let width = (BODY_SIZE[0] - 4.0) * f32::from(charge) / 100.0;
+        draw_list
+            .add_rect(
+                [x + 2.0, y + 2.0],
+                [x + 2.0 + width, body_end[1] - 2.0],
+                color,
+            )
+            .filled(true)
+            .build();
+    }
+
+    ui.dummy([BODY_SIZE[0] + TIP_SIZE[0], ui.text_line_height()]);
+    ui.same_line();
+    ui.text(power_label(power));
+}
+
+fn power_label(power: PowerInfo) -> String {
+    match power {
+        PowerInfo::Unknown => String::from("Unknown"),
+        PowerInfo::Wired => String::from("Wired"),
+        PowerInfo::Discharging(charge) => format!("{charge}%"),
+        PowerInfo::Charging(charge) => format!("Charging {charge}%"),
+        PowerInfo::Charged => String::from("Charged"),
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Buttons that, held together, trigger an app-defined action instead of imgui keys.
+///
+/// When recognized, a [`GamepadEvent::Chord`](crate::GamepadEvent::Chord) is queued, even
+/// while the game gets the gamepad's inputs. While imgui gets them, the presses of the
+/// chord's buttons are held back for [`GamepadConfig::chord_time`], and only reach imgui if
+/// the chord doesn't complete.
+///
+/// [`GamepadConfig::chord_time`]: crate::GamepadConfig::chord_time
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Chord {
+    pub buttons: Vec<gilrs::Button>,
+    pub action: String,
+}
+
+impl Chord {
+    pub fn new(buttons: impl Into<Vec<gilrs::Button>>, action: impl Into<String>) -> Chord {
+        Chord {
+            buttons: buttons.into(),
+            action: action.into(),
+        }
+    }
+
+    /// Whether `button` is the last button needed to complete the chord
+    pub(crate) fn completed_by(
+        &self,
+        button: gilrs::Button,
+        pressed_buttons: &HashSet<gilrs::Button>,
+    ) -> bool {
+        self.buttons.contains(&button)
+            && self
+                .buttons
+                .iter()
+                .all(|button| pressed_buttons.contains(button))
+    }
+}
+
+/// What happens to an event once chords are recognized
+pub(crate) enum ChordStep {
+    /// The event completed the chord with this action
+    Chord(String),
+    /// The event belongs to a chord, or may start one
+    Swallowed,
+    /// Events that go on: the held back presses of a chord that failed, then the event
+    Pass(Vec<gilrs::Event>),
+}
@@ -0,0 +1,322 @@
+use imgui::Key;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Chord, Debounce, FilterStage, GamepadMapping, Gesture, GestureTimings, HybridMode,
+    InputArbitration, InputTuning, NavHaptics, NavRepeat, Shortcut, StickDirections,
+    StickSmoothing, Turbo,
+};
+
+/// Version of the [`GamepadConfig`] schema, increased on incompatible changes
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Range of the `Axis::LeftZ`/`Axis::RightZ` values reported by triggers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TriggerAxisRange {
+    /// 0 at rest, 1 fully pressed. Negative values are treated as 0
+    #[default]
+    ZeroToOne,
+    /// -1 at rest, 1 fully pressed
+    MinusOneToOne,
+}
+
+impl TriggerAxisRange {
+    /// Brings `value` into the 0..1 range
+    pub fn normalize(self, value: f32) -> f32 {
+        match self {
+            TriggerAxisRange::ZeroToOne => value.clamp(0.0, 1.0),
+            TriggerAxisRange::MinusOneToOne => ((value + 1.0) / 2.0).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Brings a 0..1 `value` back into the range
+    pub fn denormalize(self, value: f32) -> f32 {
+        match self {
+            TriggerAxisRange::ZeroToOne => value,
+            TriggerAxisRange::MinusOneToOne => value * 2.0 - 1.0,
+        }
+    }
+}
+
+/// Everything that can be tuned about how gamepad inputs reach imgui
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GamepadConfig {
+    pub version: u32,
+    pub mapping: GamepadMapping,
+    /// Stick and D-pad axis values closer to 0 than this are considered at rest
+    pub dead_zone: f32,
+    /// L2/R2 values higher than this are considered pressed
+    pub trigger_threshold: f32,
+    pub trigger_axis_range: TriggerAxisRange,
+    pub stick_directions: StickDirections,
+    /// Response curves, inversion, sensitivity and scale of the sticks and triggers
+    pub tuning: InputTuning,
+    /// Jitter filters of the sticks
+    pub smoothing: StickSmoothing,
+    pub debounce: Debounce,
+    pub turbo: Turbo,
+    /// Key repeat of the D-pad and stick directions, `None` leaves it to imgui
+    pub nav_repeat: Option<NavRepeat>,
+    /// Filters the gamepad's events go through before reaching imgui, in order
+    pub filters: Vec<FilterStage>,
+    /// Battery charge in percent below which [`GamepadEvent::LowBattery`] is emitted
+    ///
+    /// [`GamepadEvent::LowBattery`]: crate::GamepadEvent::LowBattery
+    pub low_battery_level: u8,
+    pub chords: Vec<Chord>,
+    /// Seconds the first buttons of a chord are held back from imgui, waiting for the others
+    pub chord_time: f32,
+    pub shortcuts: Vec<Shortcut>,
+    pub gestures: Vec<Gesture>,
+    pub gesture_timings: GestureTimings,
+    /// Rumbles played on UI navigation, `None` disables them
+    pub nav_haptics: Option<NavHaptics>,
+    pub arbitration: InputArbitration,
+    pub hybrid: HybridMode,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        GamepadConfig {
+            version: CONFIG_VERSION,
+            mapping: GamepadMapping::default(),
+            dead_zone: 0.01,
+            trigger_threshold: 0.01,
+            trigger_axis_range: TriggerAxisRange::default(),
+            stick_directions: StickDirections::default(),
+            tuning: InputTuning::default(),
+            smoothing: StickSmoothing::default(),
+            debounce: Debounce::default(),
+            turbo: Turbo::default(),
+            nav_repeat: None,
+            filters: FilterStage::defaults(),
+            low_battery_level: 20,
+            chords: Vec::new(),
+            chord_time: 0.2,
+            shortcuts: Vec::new(),
+            gestures: Vec::new(),
+            gesture_timings: GestureTimings::default(),
+            nav_haptics: None,
+            arbitration: InputArbitration::default(),
+            hybrid: HybridMode::default(),
+        }
+    }
+}
+
+impl GamepadConfig {
+    /// Drives imgui through keyboard nav, see [`GamepadMapping::keyboard_nav()`].
+    ///
+    /// LB sends Shift+Tab.
+    pub fn keyboard_nav() -> GamepadConfig {
+        GamepadConfig {
+            mapping: GamepadMapping::keyboard_nav(),
+            shortcuts: vec![Shortcut::new(
+                gilrs::Button::LeftTrigger,
+                [Key::ModShift, Key::Tab],
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use file::ConfigError;
+
+#[cfg(feature = "serde")]
+mod file {
+    use std::{fmt, fs, io, path::Path};
+
+    use super::{GamepadConfig, CONFIG_VERSION};
+
+    #[derive(Debug)]
+    pub enum ConfigError {
+        Io(io::Error),
+        TomlDe(toml::de::Error),
+        TomlSer(toml::ser::Error),
+        RonDe(ron::error::SpannedError),
+        RonSer(ron::Error),
+        /// The file was written by a newer version of the crate
+        UnsupportedVersion(u32),
+        /// The file extension is neither `toml` nor `ron`
+        UnknownFormat,
+    }
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ConfigError::Io(e) => write!(f, "couldn't access the config file: {e}"),
+                ConfigError::TomlDe(e) => write!(f, "invalid TOML config: {e}"),
+                ConfigError::TomlSer(e) => write!(f, "couldn't write TOML config: {e}"),
+                ConfigError::RonDe(e) => write!(f, "invalid RON config: {e}"),
+                ConfigError::RonSer(e) => write!(f, "couldn't write RON config: {e}"),
+                ConfigError::UnsupportedVersion(version) => write!(
+                    f,
+                    "config version {version} is newer than the supported version {CONFIG_VERSION}"
+                ),
+                ConfigError::UnknownFormat => write!(f, "config files must be .toml or .ron"),
+            }
+        }
+    }
+
+    impl std::error::Error for ConfigError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                ConfigError::Io(e) => Some(e),
+                ConfigError::TomlDe(e) => Some(e),
+                ConfigError::TomlSer(e) => Some(e),
+                ConfigError::RonDe(e) => Some(e),
+                ConfigError::RonSer(e) => Some(e),
+                ConfigError::UnsupportedVersion(_) | ConfigError::UnknownFormat => None,
+            }
+        }
+    }
+
+    impl From<io::Error> for ConfigError {
+        fn from(e: io::Error) -> Self {
+            ConfigError::Io(e)
+        }
+    }
+
+    enum Format {
+        Toml,
+        Ron,
+    }
+
+    impl Format {
+        fn from_path(path: &Path) -> Result<Format, ConfigError> {
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => Ok(Format::Toml),
+                Some("ron") => Ok(Format::Ron),
+                _ => Err(ConfigError::UnknownFormat),
+            }
+        }
+    }
+
+    impl GamepadConfig {
+        pub fn from_toml(s: &str) -> Result<GamepadConfig, ConfigError> {
+            toml::from_str(s)
+                .map_err(ConfigError::TomlDe)
+                .and_then(GamepadConfig::check_version)
+        }
+
+        pub fn to_toml(&self) -> Result<String, ConfigError> {
+            toml::to_string_pretty(self).map_err(ConfigError::TomlSer)
+        }
+
+        pub fn from_ron(s: &str) -> Result<GamepadConfig, ConfigError> {
+            ron::from_str(s)
+                .map_err(ConfigError::RonDe)
+                .and_then(GamepadConfig::check_version)
+        }
+
+        pub fn to_ron(&self) -> Result<String, ConfigError> {
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(ConfigError::RonSer)
+        }
+
+        /// Loads a `.toml` or `.ron` config file
+        pub fn load(path: impl AsRef<Path>) -> Result<GamepadConfig, ConfigError> {
+            let path = path.as_ref();
+            let format = Format::from_path(path)?;
+            let contents = fs::read_to_string(path)?;
+            match format {
+                Format::Toml => GamepadConfig::from_toml(&contents),
+                Format::Ron => GamepadConfig::from_ron(&contents),
+            }
+        }
+
+        /// Saves the config as `.toml` or `.ron`, depending on the extension of `path`
+        pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+            let path = path.as_ref();
+            let contents = match Format::from_path(path)? {
+                Format::Toml => self.to_toml()?,
+                Format::Ron => self.to_ron()?,
+            };
+            fs::write(path, contents)?;
+            Ok(())
+        }
+
+        fn check_version(self) -> Result<GamepadConfig, ConfigError> {
+            if self.version > CONFIG_VERSION {
+                Err(ConfigError::UnsupportedVersion(self.version))
+            } else {
+                Ok(self)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use imgui::MouseButton;
+
+    use super::*;
+    use crate::{GestureKind, GestureTarget, NavRepeat, Smoothing};
+
+    /// A config that differs from the default in most fields
+    fn custom_config() -> GamepadConfig {
+        let mut config = GamepadConfig::keyboard_nav();
+        config.dead_zone = 0.2;
+        config.stick_directions = StickDirections::eight_way();
+        config.smoothing.left = Smoothing::one_euro();
+        config.debounce.stable_time = 0.02;
+        config.debounce.buttons.insert(gilrs::Button::South, 0.05);
+        config.turbo.buttons.push(gilrs::Button::West);
+        config.nav_repeat = Some(NavRepeat::default());
+        config.chords.push(Chord::new(
+            [gilrs::Button::Select, gilrs::Button::Start],
+            "debug",
+        ));
+        config.gestures.push(Gesture::new(
+            gilrs::Button::South,
+            GestureKind::LongPress,
+            GestureTarget::Mouse(MouseButton::Right),
+        ));
+        config.nav_haptics = Some(NavHaptics::default());
+        config
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        for config in [GamepadConfig::default(), custom_config()] {
+            let toml = config.to_toml().unwrap();
+            assert_eq!(GamepadConfig::from_toml(&toml).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        for config in [GamepadConfig::default(), custom_config()] {
+            let ron = config.to_ron().unwrap();
+            assert_eq!(GamepadConfig::from_ron(&ron).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let config = GamepadConfig::from_toml("dead_zone = 0.3").unwrap();
+        assert_eq!(config.dead_zone, 0.3);
+        assert_eq!(config.mapping, GamepadMapping::default());
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let version = CONFIG_VERSION + 1;
+        let toml = format!("version = {version}");
+        assert!(matches!(
+            GamepadConfig::from_toml(&toml),
+            Err(ConfigError::UnsupportedVersion(v)) if v == version
+        ));
+        let ron = format!("(version: {version})");
+        assert!(matches!(
+            GamepadConfig::from_ron(&ron),
+            Err(ConfigError::UnsupportedVersion(v)) if v == version
+        ));
+        let current = format!("version = {CONFIG_VERSION}");
+        assert!(GamepadConfig::from_toml(&current).is_ok());
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use gilrs::{ev::Code, GamepadId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{seconds, GamepadConfig, InputFilter};
+
+/// Ignores buttons that chatter, pressing and releasing several times in a row
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Debounce {
+    /// Minimum time in seconds a button stays pressed or released, 0 disables debouncing
+    pub stable_time: f32,
+    /// Stable time of specific buttons, overriding `stable_time`
+    pub buttons: HashMap<gilrs::Button, f32>,
+}
+
+impl Debounce {
+    fn stable_time(&self, button: gilrs::Button) -> Option<Duration> {
+        let stable_time = self
+            .buttons
+            .get(&button)
+            .copied()
+            .unwrap_or(self.stable_time);
+        (stable_time > 0.0).then(|| seconds(stable_time))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ButtonState {
+    /// State of the physical button
+    pressed: bool,
+    /// State sent to the next filters
+    reported: bool,
+    /// When `reported` last changed
+    changed: SystemTime,
+}
+
+impl Default for ButtonState {
+    fn default() -> Self {
+        ButtonState {
+            pressed: false,
+            reported: false,
+            changed: SystemTime::UNIX_EPOCH,
+        }
+    }
+}
+
+impl ButtonState {
+    /// Whether a change of the physical button goes through
+    fn change(&mut self, pressed: bool, time: SystemTime, stable_time: Duration) -> bool {
+        self.pressed = pressed;
+        let stable = time.duration_since(self.changed).unwrap_or_default() >= stable_time;
+        if pressed == self.reported || !stable {
+            return false;
+        }
+        self.reported = pressed;
+        self.changed = time;
+        true
+    }
+
+    /// Whether the last change of the physical button goes through now that it's stable
+    fn settle(&mut self, now: SystemTime, stable_time: Duration) -> bool {
+        if self.pressed == self.reported
+            || now.duration_since(self.changed).unwrap_or_default() < stable_time
+        {
+            return false;
+        }
+        self.reported = self.pressed;
+        self.changed = now;
+        true
+    }
+}
+
+/// [`crate::FilterStage::Debounce`]
+#[derive(Debug, Default)]
+pub(crate) struct DebounceFilter {
+    /// State of each button by code, so that unknown buttons don't share one, along with
+    /// the button of its last event
+    buttons: HashMap<(GamepadId, Code), (gilrs::Button, ButtonState)>,
+}
+
+pub(crate) fn button_event(button: gilrs::Button, code: Code, pressed: bool) -> gilrs::EventType {
+    if pressed {
+        gilrs::EventType::ButtonPressed(button, code)
+    } else {
+        gilrs::EventType::ButtonReleased(button, code)
+    }
+}
+
+impl InputFilter for DebounceFilter {
+    /// The first change goes through right away, the following ones only once the button
+    /// was stable long enough
+    fn filter(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event> {
+        let (button, code, pressed) = match event.event {
+            gilrs::EventType::ButtonPressed(button, code) => (button, code, true),
+            gilrs::EventType::ButtonReleased(button, code) => (button, code, false),
+            _ => return vec![event],
+        };
+        let Some(stable_time) = config.debounce.stable_time(button) else {
+            return vec![event];
+        };
+
+        let (last_button, state) = self
+            .buttons
+            .entry((event.id, code))
+            .or_insert((button, ButtonState::default()));
+        *last_button = button;
+        if state.change(pressed, event.time, stable_time) {
+            vec![event]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Sends the last change of buttons that became stable
+    fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let mut events = Vec::new();
+        for (&(state_id, code), (button, state)) in &mut self.buttons {
+            let stable_time = config.debounce.stable_time(*button).unwrap_or_default();
+            if state_id != id || !state.settle(now, stable_time) {
+                continue;
+            }
+            let mut event = gilrs::Event::new(id, button_event(*button, code, state.pressed));
+            event.time = now;
+            events.push(event);
+        }
+        events
+    }
+
+    fn reset(&mut self, id: GamepadId) {
+        self.buttons.retain(|(state_id, _), _| *state_id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1000) + Duration::from_millis(ms)
+    }
+
+    const STABLE: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn first_change_goes_through() {
+        let mut state = ButtonState::default();
+        assert!(state.change(true, at(0), STABLE));
+        assert!(state.reported);
+    }
+
+    #[test]
+    fn chatter_is_ignored() {
+        let mut state = ButtonState::default();
+        assert!(state.change(true, at(0), STABLE));
+        assert!(!state.change(false, at(5), STABLE));
+        assert!(!state.change(true, at(10), STABLE));
+        // Back to the reported state, nothing to send
+        assert!(!state.settle(at(50), STABLE));
+        assert!(state.reported);
+    }
+
+    #[test]
+    fn change_after_stable_time_goes_through() {
+        let mut state = ButtonState::default();
+        assert!(state.change(true, at(0), STABLE));
+        assert!(state.change(false, at(20), STABLE));
+        assert!(!state.reported);
+    }
+
+    #[test]
+    fn last_change_is_sent_once_stable() {
+        let mut state = ButtonState::default();
+        assert!(state.change(true, at(0), STABLE));
+        assert!(!state.change(false, at(5), STABLE));
+        assert!(!state.settle(at(15), STABLE));
+        assert!(state.settle(at(20), STABLE));
+        assert!(!state.reported);
+        assert!(!state.settle(at(60), STABLE));
+    }
+
+    #[test]
+    fn stable_time_of_buttons() {
+        let debounce = Debounce {
+            stable_time: 0.02,
+            buttons: HashMap::from([(gilrs::Button::South, 0.05), (gilrs::Button::East, 0.0)]),
+        };
+        assert_eq!(
+            debounce.stable_time(gilrs::Button::North),
+            Some(Duration::from_secs_f32(0.02))
+        );
+        assert_eq!(
+            debounce.stable_time(gilrs::Button::South),
+            Some(Duration::from_secs_f32(0.05))
+        );
+        assert_eq!(debounce.stable_time(gilrs::Button::East), None);
+        let broken = Debounce {
+            stable_time: f32::NAN,
+            buttons: HashMap::from([(gilrs::Button::South, f32::INFINITY)]),
+        };
+        assert_eq!(broken.stable_time(gilrs::Button::North), None);
+        assert_eq!(
+            broken.stable_time(gilrs::Button::South),
+            Some(Duration::MAX)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use imgui::{DrawListMut, ImColor32, Ui};
+
+use crate::{
+    battery_indicator, GamepadConfig, GamepadHandler, GamepadState, GAMEPAD_KEYS,
+    KEYBOARD_NAV_KEYS, MODIFIER_KEYS,
+};
+
+const STICK_RADIUS: f32 = 30.0;
+const TRIGGER_SIZE: [f32; 2] = [12.0, 2.0 * STICK_RADIUS];
+const BUTTON_RADIUS: f32 = 6.0;
+const BUTTON_CELL: [f32; 2] = [56.0, 20.0];
+const BUTTONS_PER_ROW: usize = 6;
+const SPACING: f32 = 12.0;
+
+const OUTLINE_COLOR: ImColor32 = ImColor32::from_rgb(160, 160, 160);
+const DEAD_ZONE_COLOR: ImColor32 = ImColor32::from_rgb(200, 80, 80);
+const ACTIVE_COLOR: ImColor32 = ImColor32::from_rgb(80, 200, 120);
+
+const BUTTONS: [(gilrs::Button, &str); 18] = [
+    (gilrs::Button::South, "South"),
+    (gilrs::Button::East, "East"),
+    (gilrs::Button::North, "North"),
+    (gilrs::Button::West, "West"),
+    (gilrs::Button::LeftTrigger, "L1"),
+    (gilrs::Button::RightTrigger, "R1"),
+    (gilrs::Button::LeftTrigger2, "L2"),
+    (gilrs::Button::RightTrigger2, "R2"),
+    (gilrs::Button::LeftThumb, "L3"),
+    (gilrs::Button::RightThumb, "R3"),
+    (gilrs::Button::Select, "Select"),
+    (gilrs::Button::Start, "Start"),
+    (gilrs::Button::Mode, "Mode"),
+    (gilrs::Button::C, "C"),
+    (gilrs::Button::DPadUp, "Up"),
+    (gilrs::Button::DPadDown, "Down"),
+    (gilrs::Button::DPadLeft, "Left"),
+    (gilrs::Button::DPadRight, "Right"),
+];
+
+impl GamepadHandler {
+    /// Draws a window with the state of every connected gamepad, as seen by the handler,
+    /// and the imgui gamepad keys currently down.
+    pub fn debug_window(&self, ui: &Ui) {
+        ui.window("Gamepad debug").build(|| {
+            let mut ids: Vec<_> = self.connected_controllers.keys().copied().collect();
+            ids.sort_by_key(|id| usize::from(*id));
+
+            if ids.is_empty() {
+                ui.text_disabled("No gamepads connected");
+            }
+
+            for id in ids {
+                let info = self.gamepad_info(id);
+                let active = if self.active_gamepad == Some(id) {
+                    " (active)"
+                } else {
+                    ""
+                };
+                let player = match self.player_slot(id) {
+                    Some(slot) => format!(" P{}", slot + 1),
+                    None => String::new(),
+                };
+                ui.text(format!("{id}: {}{player}{active}", info.name));
+                if let Some(profile) = self.gamepad_profile(id) {
+                    ui.text_disabled(format!("Profile: {profile}"));
+                }
+                battery_indicator(ui, info.power);
+                draw_gamepad(
+                    ui,
+                    self.gamepad_config(id),
+                    &self.connected_controllers[&id],
+                );
+                ui.separator();
+            }
+
+            ui.text("imgui keys down:");
+            let keys = GAMEPAD_KEYS
+                .into_iter()
+                .chain(KEYBOARD_NAV_KEYS)
+                .chain(MODIFIER_KEYS);
+            for key in keys {
+                if ui.is_key_down(key) {
+                    ui.bullet_text(format!("{key:?}"));
+                }
+            }
+        });
+    }
+}
+
+fn draw_gamepad(ui: &Ui, config: &GamepadConfig, gamepad: &GamepadState) {
+    let [x, y] = ui.cursor_screen_pos();
+    let draw_list = ui.get_window_draw_list();
+
+    let l2_pos = [x, y];
+    let l_stick_center = [
+        x + TRIGGER_SIZE[0] + SPACING + STICK_RADIUS,
+        y + STICK_RADIUS,
+    ];
+    let r_stick_center = [
+        l_stick_center[0] + 2.0 * STICK_RADIUS + SPACING,
+        y + STICK_RADIUS,
+    ];
+    let r2_pos = [r_stick_center[0] + STICK_RADIUS + SPACING, y];
+    draw_trigger(&draw_list, config, l2_pos, gamepad.l2.max(gamepad.lz));
+    draw_stick(&draw_list, config, l_stick_center, gamepad.lx, gamepad.ly);
+    draw_stick(&draw_list, config, r_stick_center, gamepad.rx, gamepad.ry);
+    draw_trigger(&draw_list, config, r2_pos, gamepad.r2.max(gamepad.rz));
+
+    let buttons_y = y + 2.0 * STICK_RADIUS + SPACING;
+    for (i, (button, label)) in BUTTONS.iter().enumerate() {
+        let column = (i % BUTTONS_PER_ROW) as f32;
+        let row = (i / BUTTONS_PER_ROW) as f32;
+        let center = [
+            x + BUTTON_RADIUS + column * BUTTON_CELL[0],
+            buttons_y + BUTTON_RADIUS + row * BUTTON_CELL[1],
+        ];
+        let pressed = gamepad.pressed_buttons.contains(button);
+        let color = if pressed { ACTIVE_COLOR } else { OUTLINE_COLOR };
+        draw_list
+            .add_circle(center, BUTTON_RADIUS, color)
+            .filled(pressed)
+            .build();
+        draw_list.add_text(
+            [center[0] + BUTTON_RADIUS + 3.0, center[1] - BUTTON_RADIUS],
+            OUTLINE_COLOR,
+            label,
+        );
+    }
+
+    let rows = BUTTONS.len().div_ceil(BUTTONS_PER_ROW) as f32;
+    let width = r2_pos[0] + TRIGGER_SIZE[0] - x;
+    let width = width.max(BUTTONS_PER_ROW as f32 * BUTTON_CELL[0]);
+    let height = buttons_y + rows * BUTTON_CELL[1] - y;
+    ui.dummy([width, height]);
+}
+
+fn draw_stick(draw_list: &DrawListMut, config: &GamepadConfig, center: [f32; 2], x: f32, y: f32) {
+    draw_list
+        .add_circle(center, STICK_RADIUS, OUTLINE_COLOR)
+        .build();
+    draw_list
+        .add_circle(center, STICK_RADIUS * config.dead_zone, DEAD_ZONE_COLOR)
+        .build();
+
+    let outside_dead_zone = x.abs() > config.dead_zone || y.abs() > config.dead_zone;
+    let color = if outside_dead_zone {
+        ACTIVE_COLOR
+    } else {
+        OUTLINE_COLOR
+    };
+    // gilrs' Y axis points up, imgui's points down
+    let position = [center[0] + x * STICK_RADIUS, center[1] - y * STICK_RADIUS];
+    draw_list
+        .add_circle(position, 4.0, color)
+        .filled(true)
+        .build();
+}
+
+fn draw_trigger(draw_list: &DrawListMut, config: &GamepadConfig, top_left: [f32; 2], value: f32) {
+    let bottom_right = [top_left[0] + TRIGGER_SIZE[0], top_left[1] + TRIGGER_SIZE[1]];
+    let fill_top = bottom_right[1] - TRIGGER_SIZE[1] * value.clamp(0.0, 1.0);
+    let color = if value > config.trigger_threshold {
+        ACTIVE_COLOR
+    } else {
+        OUTLINE_COLOR
+    };
+    draw_list
+        .add_rect([top_left[0], fill_top], bottom_right, color)
+        .filled(true)
+        .build();
+    draw_list
+        .add_rect(top_left, bottom_right, OUTLINE_COLOR)
+        .build();
+}
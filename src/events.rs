@@ -0,0 +1,92 @@
+use gilrs::{Gamepad, GamepadId, Gilrs, PowerInfo};
+
+/// Metadata of a gamepad as reported by gilrs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamepadInfo {
+    pub id: GamepadId,
+    pub name: String,
+    pub os_name: String,
+    pub uuid: [u8; 16],
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// Power state when the info was registered
+    pub power: PowerInfo,
+}
+
+impl GamepadInfo {
+    /// Placeholder used when no metadata was registered for `id`.
+    ///
+    /// See [`GamepadHandler::update_gamepad_info()`](crate::GamepadHandler::update_gamepad_info).
+    pub fn unknown(id: GamepadId) -> GamepadInfo {
+        GamepadInfo {
+            id,
+            name: String::new(),
+            os_name: String::new(),
+            uuid: [0; 16],
+            vendor_id: None,
+            product_id: None,
+            power: PowerInfo::Unknown,
+        }
+    }
+
+    pub fn from_gilrs(gilrs: &Gilrs, id: GamepadId) -> GamepadInfo {
+        GamepadInfo::from(gilrs.gamepad(id))
+    }
+
+    /// The gamepad name, or a name based on its ID if gilrs doesn't know it
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            format!("Gamepad {}", self.id)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Whether both describe the same device, ignoring the power state
+    pub(crate) fn same_device(&self, other: &GamepadInfo) -> bool {
+        GamepadInfo {
+            power: other.power,
+            ..self.clone()
+        } == *other
+    }
+}
+
+impl From<Gamepad<'_>> for GamepadInfo {
+    fn from(gamepad: Gamepad<'_>) -> Self {
+        GamepadInfo {
+            id: gamepad.id(),
+            name: gamepad.name().to_string(),
+            os_name: gamepad.os_name().to_string(),
+            uuid: gamepad.uuid(),
+            vendor_id: gamepad.vendor_id(),
+            product_id: gamepad.product_id(),
+            power: gamepad.power_info(),
+        }
+    }
+}
+
+/// High-level gamepad lifecycle events, retrieved with
+/// [`GamepadHandler::drain_events()`](crate::GamepadHandler::drain_events).
+#[derive(Debug, Clone)]
+pub enum GamepadEvent {
+    Connected(GamepadInfo),
+    Disconnected(GamepadInfo),
+    /// A different gamepad started driving the UI.
+    ///
+    /// Disconnecting the active gamepad clears it without emitting this event.
+    ActiveChanged(GamepadInfo),
+    /// The gamepad pressed a button, or pushed an axis past half its range, that isn't mapped
+    /// to any imgui key.
+    UnmappedInput(GamepadInfo, gilrs::EventType),
+    /// The gamepad runs on battery, and its charge dropped below
+    /// [`GamepadConfig::low_battery_level`](crate::GamepadConfig::low_battery_level).
+    ///
+    /// Emitted again only after the gamepad was charged.
+    LowBattery(GamepadInfo),
+    /// The buttons of a [`Chord`](crate::Chord) are held, carries its action.
+    Chord(GamepadInfo, String),
+    /// A [`Gesture`](crate::Gesture) with an action target was recognized, carries its action.
+    Gesture(GamepadInfo, String),
+    /// The gamepad took a player slot (0 for P1), or got its slot back after reconnecting.
+    PlayerJoined(GamepadInfo, usize),
+}
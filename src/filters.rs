@@ -0,0 +1,187 @@
+use std::{collections::HashMap, fmt, time::SystemTime};
+
+use gilrs::GamepadId;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    debounce::DebounceFilter, repeat::RepeatFilter, smoothing::SmoothingFilter,
+    tuning::TuningFilter, turbo::TurboFilter, GamepadConfig,
+};
+
+/// A step between the gilrs events and imgui, which can change, drop or add events.
+///
+/// Add custom filters with [`GamepadHandler::add_filter()`], and list them in
+/// [`GamepadConfig::filters`] to use them.
+///
+/// [`GamepadHandler::add_filter()`]: crate::GamepadHandler::add_filter
+pub trait InputFilter: fmt::Debug {
+    /// Returns the events that go on to the next filter, usually `event` itself.
+    ///
+    /// `config` is the config of the gamepad that sent the event.
+    fn filter(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event>;
+
+    /// Called once per frame for every connected gamepad by
+    /// [`GamepadHandler::update()`](crate::GamepadHandler::update), returns events to send.
+    fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let _ = (id, config, now);
+        Vec::new()
+    }
+
+    /// Called when imgui stops getting the gamepad's inputs, e.g. when it disconnects or the
+    /// game takes over. The filter must forget the inputs it holds.
+    fn reset(&mut self, id: GamepadId) {
+        let _ = id;
+    }
+}
+
+/// A filter of the pipeline, see [`GamepadConfig::filters`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FilterStage {
+    /// Applies [`GamepadConfig::debounce`]. Wherever it's listed, it runs before the other
+    /// stages and before chords, the UI mode toggle and the player join flow see the event
+    Debounce,
+    /// Applies [`GamepadConfig::smoothing`]
+    Smoothing,
+    /// Applies [`GamepadConfig::tuning`]
+    Tuning,
+    /// Applies [`GamepadConfig::turbo`]
+    Turbo,
+    /// Applies [`GamepadConfig::nav_repeat`]
+    NavRepeat,
+    /// A filter added with [`GamepadHandler::add_filter()`](crate::GamepadHandler::add_filter)
+    Custom(String),
+}
+
+impl FilterStage {
+    /// Debounce, smoothing, tuning, turbo, then nav repeat
+    pub fn defaults() -> Vec<FilterStage> {
+        vec![
+            FilterStage::Debounce,
+            FilterStage::Smoothing,
+            FilterStage::Tuning,
+            FilterStage::Turbo,
+            FilterStage::NavRepeat,
+        ]
+    }
+}
+
+/// Instances of every filter, shared by the gamepads
+#[derive(Debug, Default)]
+pub(crate) struct FilterPipeline {
+    debounce: DebounceFilter,
+    smoothing: SmoothingFilter,
+    tuning: TuningFilter,
+    turbo: TurboFilter,
+    repeat: RepeatFilter,
+    custom: HashMap<String, Box<dyn InputFilter>>,
+}
+
+impl FilterPipeline {
+    pub(crate) fn add(&mut self, name: String, filter: Box<dyn InputFilter>) {
+        self.custom.insert(name, filter);
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Box<dyn InputFilter>> {
+        self.custom.remove(name)
+    }
+
+    /// Runs [`FilterStage::Debounce`] if `config` lists it, before any other handling
+    pub(crate) fn debounce(
+        &mut self,
+        config: &GamepadConfig,
+        event: gilrs::Event,
+    ) -> Vec<gilrs::Event> {
+        if config.filters.contains(&FilterStage::Debounce) {
+            self.debounce.filter(config, event)
+        } else {
+            vec![event]
+        }
+    }
+
+    /// Changes held back by [`FilterStage::Debounce`] that became stable
+    pub(crate) fn update_debounce(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        if config.filters.contains(&FilterStage::Debounce) {
+            self.debounce.update(id, config, now)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Stages without a filter are skipped, debounce runs on its own with `debounce()`
+    fn stage(&mut self, stage: &FilterStage) -> Option<&mut (dyn InputFilter + 'static)> {
+        match stage {
+            FilterStage::Debounce => None,
+            FilterStage::Smoothing => Some(&mut self.smoothing),
+            FilterStage::Tuning => Some(&mut self.tuning),
+            FilterStage::Turbo => Some(&mut self.turbo),
+            FilterStage::NavRepeat => Some(&mut self.repeat),
+            FilterStage::Custom(name) => self.custom.get_mut(name).map(|filter| &mut **filter),
+        }
+    }
+
+    /// Sends `events` through the stages of `config`, starting at `first_stage`
+    fn run_from(
+        &mut self,
+        config: &GamepadConfig,
+        first_stage: usize,
+        mut events: Vec<gilrs::Event>,
+    ) -> Vec<gilrs::Event> {
+        for stage in config.filters.iter().skip(first_stage) {
+            let Some(filter) = self.stage(stage) else {
+                continue;
+            };
+            events = events
+                .into_iter()
+                .flat_map(|event| filter.filter(config, event))
+                .collect();
+        }
+        events
+    }
+
+    pub(crate) fn run(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event> {
+        self.run_from(config, 0, vec![event])
+    }
+
+    /// Events added by the filters, they go through the stages that follow
+    pub(crate) fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let mut events = Vec::new();
+        for (index, stage) in config.filters.iter().enumerate() {
+            let Some(filter) = self.stage(stage) else {
+                continue;
+            };
+            let added = filter.update(id, config, now);
+            if !added.is_empty() {
+                events.extend(self.run_from(config, index + 1, added));
+            }
+        }
+        events
+    }
+
+    pub(crate) fn reset(&mut self, id: GamepadId) {
+        self.debounce.reset(id);
+        self.smoothing.reset(id);
+        self.tuning.reset(id);
+        self.turbo.reset(id);
+        self.repeat.reset(id);
+        for filter in self.custom.values_mut() {
+            filter.reset(id);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::time::{Duration, SystemTime};
+
+use gilrs::ev::Code;
+use imgui::{Io, Key, MouseButton};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{seconds, GamepadConfig, InputOutcome};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GestureKind {
+    Tap,
+    DoubleTap,
+    LongPress,
+}
+
+/// What happens when a gesture is recognized
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GestureTarget {
+    /// Presses and releases an imgui key
+    Key(#[cfg_attr(feature = "serde", serde(with = "crate::imgui_serde::key"))] Key),
+    /// Presses and releases an imgui mouse button, e.g. `MouseButton::Right` to open context menus
+    Mouse(
+        #[cfg_attr(feature = "serde", serde(with = "crate::imgui_serde::mouse_button"))]
+        MouseButton,
+    ),
+    /// Queues a [`GamepadEvent::Gesture`](crate::GamepadEvent::Gesture) with this action
+    Action(String),
+}
+
+impl GestureTarget {
+    fn fire(&self, io: &mut Io) -> InputOutcome {
+        match self {
+            GestureTarget::Key(key) => {
+                io.add_key_event(*key, true);
+                io.add_key_event(*key, false);
+                InputOutcome::Pressed
+            }
+            GestureTarget::Mouse(button) => {
+                io.add_mouse_button_event(*button, true);
+                io.add_mouse_button_event(*button, false);
+                InputOutcome::Clicked(*button)
+            }
+            GestureTarget::Action(action) => InputOutcome::Gesture(action.clone()),
+        }
+    }
+}
+
+/// Binds a tap, double tap or long press of a button.
+///
+/// Buttons with gestures only reach imgui once their press has been classified: a tap
+/// without a [`GestureKind::Tap`] gesture sends a short press of the button's mapped key,
+/// so those buttons can't be held down in imgui.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gesture {
+    pub button: gilrs::Button,
+    pub kind: GestureKind,
+    pub target: GestureTarget,
+}
+
+impl Gesture {
+    pub fn new(button: gilrs::Button, kind: GestureKind, target: GestureTarget) -> Gesture {
+        Gesture {
+            button,
+            kind,
+            target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GestureTimings {
+    /// Maximum time in seconds between the release of a tap and the press of the second tap
+    pub double_tap_time: f32,
+    /// Minimum time in seconds a button must be held to be a long press
+    pub long_press_time: f32,
+}
+
+impl GestureTimings {
+    fn double_tap(&self) -> Duration {
+        seconds(self.double_tap_time)
+    }
+
+    fn long_press(&self) -> Duration {
+        seconds(self.long_press_time)
+    }
+}
+
+impl Default for GestureTimings {
+    fn default() -> Self {
+        GestureTimings {
+            double_tap_time: 0.3,
+            long_press_time: 0.5,
+        }
+    }
+}
+
+pub(crate) fn has_gestures(config: &GamepadConfig, button: gilrs::Button) -> bool {
+    config
+        .gestures
+        .iter()
+        .any(|gesture| gesture.button == button)
+}
+
+fn find_gesture(
+    config: &GamepadConfig,
+    button: gilrs::Button,
+    kind: GestureKind,
+) -> Option<&Gesture> {
+    config
+        .gestures
+        .iter()
+        .find(|gesture| gesture.button == button && gesture.kind == kind)
+}
+
+fn fire(
+    io: &mut Io,
+    config: &GamepadConfig,
+    button: gilrs::Button,
+    code: Code,
+    kind: GestureKind,
+) -> InputOutcome {
+    match find_gesture(config, button, kind) {
+        Some(gesture) => gesture.target.fire(io),
+        None if kind == GestureKind::Tap => match config.mapping.button_key(button, code) {
+            Some(key) => GestureTarget::Key(key).fire(io),
+            None => InputOutcome::Idle,
+        },
+        None => InputOutcome::Idle,
+    }
+}
+
+fn elapsed(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or_default()
+}
+
+/// Gesture recognition of a single button
+#[derive(Debug)]
+pub(crate) struct GestureState {
+    /// Code of the button's last press
+    code: Code,
+    pressed_at: Option<SystemTime>,
+    /// The current press was already classified, its release does nothing
+    handled: bool,
+    /// Release time of a tap that may become a double tap
+    pending_tap: Option<SystemTime>,
+}
+
+impl GestureState {
+    pub(crate) fn new(code: Code) -> GestureState {
+        GestureState {
+            code,
+            pressed_at: None,
+            handled: false,
+            pending_tap: None,
+        }
+    }
+
+    pub(crate) fn press(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        code: Code,
+        time: SystemTime,
+    ) -> InputOutcome {
+        let timings = config.gesture_timings;
+        let mut outcome = InputOutcome::Idle;
+        if let Some(released_at) = self.pending_tap.take() {
+            if elapsed(released_at, time) <= timings.double_tap() {
+                self.code = code;
+                self.pressed_at = Some(time);
+                self.handled = true;
+                return fire(io, config, button, code, GestureKind::DoubleTap);
+            }
+            outcome = fire(io, config, button, self.code, GestureKind::Tap);
+        }
+
+        self.code = code;
+        self.pressed_at = Some(time);
+        self.handled = false;
+        outcome
+    }
+
+    pub(crate) fn release(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        time: SystemTime,
+    ) -> InputOutcome {
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return InputOutcome::Idle;
+        };
+        if self.handled {
+            return InputOutcome::Idle;
+        }
+
+        let long_press = config.gesture_timings.long_press();
+        let has_long_press = find_gesture(config, button, GestureKind::LongPress).is_some();
+        if has_long_press && elapsed(pressed_at, time) >= long_press {
+            fire(io, config, button, self.code, GestureKind::LongPress)
+        } else if find_gesture(config, button, GestureKind::DoubleTap).is_some() {
+            self.pending_tap = Some(time);
+            InputOutcome::Idle
+        } else {
+            fire(io, config, button, self.code, GestureKind::Tap)
+        }
+    }
+
+    /// Recognizes the gestures that don't need another event: long presses while the button
+    /// is held, and taps that can no longer become double taps.
+    pub(crate) fn update(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        now: SystemTime,
+    ) -> InputOutcome {
+        let timings = config.gesture_timings;
+        if let Some(pressed_at) = self.pressed_at {
+            let has_long_press = find_gesture(config, button, GestureKind::LongPress).is_some();
+            if !self.handled && has_long_press && elapsed(pressed_at, now) >= timings.long_press() {
+                self.handled = true;
+                return fire(io, config, button, self.code, GestureKind::LongPress);
+            }
+        }
+
+        if let Some(released_at) = self.pending_tap {
+            if elapsed(released_at, now) > timings.double_tap() {
+                self.pending_tap = None;
+                return fire(io, config, button, self.code, GestureKind::Tap);
+            }
+        }
+
+        InputOutcome::Idle
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks},
+    GamepadId, Gilrs,
+};
+use imgui::Ui;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GamepadMapping, PhysicalInput};
+
+/// Inputs that move the nav focus, through the key they're mapped to
+const MOVE_INPUTS: [PhysicalInput; 12] = [
+    PhysicalInput::Button(gilrs::Button::DPadLeft),
+    PhysicalInput::Button(gilrs::Button::DPadRight),
+    PhysicalInput::Button(gilrs::Button::DPadUp),
+    PhysicalInput::Button(gilrs::Button::DPadDown),
+    PhysicalInput::AxisNegative(gilrs::Axis::DPadX),
+    PhysicalInput::AxisPositive(gilrs::Axis::DPadX),
+    PhysicalInput::AxisNegative(gilrs::Axis::DPadY),
+    PhysicalInput::AxisPositive(gilrs::Axis::DPadY),
+    PhysicalInput::AxisNegative(gilrs::Axis::LeftStickX),
+    PhysicalInput::AxisPositive(gilrs::Axis::LeftStickX),
+    PhysicalInput::AxisNegative(gilrs::Axis::LeftStickY),
+    PhysicalInput::AxisPositive(gilrs::Axis::LeftStickY),
+];
+
+/// Input that activates the focused item
+const ACTIVATE_INPUT: PhysicalInput = PhysicalInput::Button(gilrs::Button::South);
+
+/// Frames without a focus change after which a nav move is considered blocked by a boundary
+const MOVE_RESULT_FRAMES: u32 = 2;
+
+/// A rumble pulse, magnitudes range from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rumble {
+    pub strong: f32,
+    pub weak: f32,
+    /// In seconds
+    pub duration: f32,
+}
+
+impl Rumble {
+    pub fn new(strong: f32, weak: f32, duration: f32) -> Rumble {
+        Rumble {
+            strong,
+            weak,
+            duration,
+        }
+    }
+}
+
+/// UI navigation happenings that can trigger a rumble
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavFeedback {
+    FocusMoved,
+    Activated,
+    /// The nav focus couldn't move further in the requested direction
+    Boundary,
+}
+
+/// Rumble played for each [`NavFeedback`], `None` disables it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NavHaptics {
+    pub focus_moved: Option<Rumble>,
+    pub activated: Option<Rumble>,
+    pub boundary: Option<Rumble>,
+}
+
+impl Default for NavHaptics {
+    fn default() -> Self {
+        NavHaptics {
+            focus_moved: Some(Rumble::new(0.0, 0.3, 0.03)),
+            activated: Some(Rumble::new(0.4, 0.4, 0.06)),
+            boundary: Some(Rumble::new(0.6, 0.0, 0.1)),
+        }
+    }
+}
+
+impl NavHaptics {
+    pub fn rumble(&self, feedback: NavFeedback) -> Option<Rumble> {
+        match feedback {
+            NavFeedback::FocusMoved => self.focus_moved,
+            NavFeedback::Activated => self.activated,
+            NavFeedback::Boundary => self.boundary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingMove {
+    /// Focused item when the move was requested
+    from: Option<[f32; 2]>,
+    frames: u32,
+}
+
+/// Detects nav feedback and plays rumbles
+#[derive(Default)]
+pub(crate) struct HapticsState {
+    /// Playing effects and when they complete at the earliest, dropping an effect stops it
+    effects: HashMap<GamepadId, (Effect, Instant)>,
+    /// Position of the focused item tracked this frame
+    focus: Option<[f32; 2]>,
+    /// Whether the app tracked any item this frame
+    tracked: bool,
+    pending_move: Option<PendingMove>,
+}
+
+impl fmt::Debug for HapticsState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HapticsState")
+            .field("playing", &self.effects.keys().collect::<Vec<_>>())
+            .field("focus", &self.focus)
+            .field("tracked", &self.tracked)
+            .field("pending_move", &self.pending_move)
+            .finish()
+    }
+}
+
+impl HapticsState {
+    pub(crate) fn track_item(&mut self, ui: &Ui) {
+        self.tracked = true;
+        if ui.is_item_focused() {
+            self.focus = Some(ui.item_rect_min());
+        }
+    }
+
+    /// Nav feedback of the current frame, `mapping` tells which keys move and activate
+    pub(crate) fn update(&mut self, ui: &Ui, mapping: &GamepadMapping) -> Vec<NavFeedback> {
+        let focus = self.focus.take();
+        let tracked = std::mem::take(&mut self.tracked);
+        let mut feedback = Vec::new();
+
+        // imgui applies nav moves one frame after the key press
+        if let Some(pending) = &mut self.pending_move {
+            if !tracked || focus != pending.from {
+                feedback.push(NavFeedback::FocusMoved);
+                self.pending_move = None;
+            } else {
+                pending.frames += 1;
+                if pending.frames >= MOVE_RESULT_FRAMES {
+                    feedback.push(NavFeedback::Boundary);
+                    self.pending_move = None;
+                }
+            }
+        }
+
+        if ui.io().nav_active {
+            let activated = mapping.key(ACTIVATE_INPUT);
+            if activated.is_some_and(|key| ui.is_key_pressed_no_repeat(key)) {
+                feedback.push(NavFeedback::Activated);
+            }
+            let moved = MOVE_INPUTS
+                .into_iter()
+                .filter_map(|input| mapping.key(input))
+                .any(|key| ui.is_key_pressed(key));
+            if moved {
+                self.pending_move = Some(PendingMove {
+                    from: focus,
+                    frames: 0,
+                });
+            }
+        }
+
+        feedback
+    }
+
+    pub(crate) fn play(&mut self, gilrs: &mut Gilrs, id: GamepadId, rumble: Rumble) {
+        let supported = gilrs
+            .connected_gamepad(id)
+            .is_some_and(|gamepad| gamepad.is_ff_supported());
+        if !supported {
+            return;
+        }
+
+        // gilrs can't play effects shorter than a tick
+        let ms = (rumble.duration * 1000.0).max(1.0) as u32;
+        let duration = Ticks::from_ms(ms);
+        let scheduling = Replay {
+            play_for: duration,
+            ..Default::default()
+        };
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: magnitude(rumble.strong),
+                },
+                scheduling,
+                ..Default::default()
+            })
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Weak {
+                    magnitude: magnitude(rumble.weak),
+                },
+                scheduling,
+                ..Default::default()
+            })
+            .gamepads(&[id])
+            .repeat(Repeat::For(duration))
+            .finish(gilrs);
+        // Haptics are best effort, failing to rumble isn't worth reporting
+        if let Ok(effect) = effect {
+            if effect.play().is_ok() {
+                let ends_at = Instant::now() + Duration::from_millis(ms.into());
+                self.effects.insert(id, (effect, ends_at));
+            }
+        }
+    }
+
+    pub(crate) fn stop(&mut self, id: GamepadId) {
+        self.effects.remove(&id);
+    }
+
+    /// Drops the gamepad's effect if it's the one that completed, and not a newer one
+    pub(crate) fn completed(&mut self, id: GamepadId) {
+        let now = Instant::now();
+        if self
+            .effects
+            .get(&id)
+            .is_some_and(|&(_, ends_at)| ends_at <= now)
+        {
+            self.effects.remove(&id);
+        }
+    }
+}
+
+fn magnitude(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
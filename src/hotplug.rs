@@ -0,0 +1,132 @@
+use std::time::Instant;
+
+use gilrs::GamepadId;
+use imgui::{Condition, Ui, WindowFlags};
+
+use crate::{seconds, GamepadEvent, GamepadInfo};
+
+const RECONNECT_POPUP: &str = "Reconnect your controller";
+/// Distance between the toasts and the bottom right corner of the display
+const TOAST_MARGIN: f32 = 16.0;
+const TOAST_SPACING: f32 = 8.0;
+const TOAST_ALPHA: f32 = 0.8;
+
+#[derive(Debug)]
+struct Toast {
+    message: String,
+    created: Instant,
+}
+
+/// Shows toasts when gamepads connect or disconnect, and optionally blocks the UI with a
+/// "Reconnect your controller" modal when the active gamepad is lost.
+///
+/// Feed it every event from [`GamepadHandler::drain_events()`](crate::GamepadHandler::drain_events)
+/// and draw it every frame, outside of any window.
+#[derive(Debug)]
+pub struct HotplugWidget {
+    /// How long each toast stays on screen, in seconds
+    pub toast_duration: f32,
+    /// Opens the modal when the active gamepad disconnects, it closes on the next input
+    pub reconnect_modal: bool,
+    toasts: Vec<Toast>,
+    active: Option<GamepadId>,
+    /// The active gamepad that disconnected, while waiting for an input
+    lost: Option<GamepadInfo>,
+    modal_open: bool,
+}
+
+impl Default for HotplugWidget {
+    fn default() -> Self {
+        HotplugWidget {
+            toast_duration: 3.0,
+            reconnect_modal: false,
+            toasts: Vec::new(),
+            active: None,
+            lost: None,
+            modal_open: false,
+        }
+    }
+}
+
+impl HotplugWidget {
+    pub fn new() -> HotplugWidget {
+        HotplugWidget::default()
+    }
+
+    pub fn handle_event(&mut self, event: &GamepadEvent) {
+        match event {
+            GamepadEvent::Connected(info) => {
+                self.push_toast(format!("{} connected", info.display_name()));
+            }
+            GamepadEvent::Disconnected(info) => {
+                self.push_toast(format!("{} disconnected", info.display_name()));
+                if self.active == Some(info.id) {
+                    self.active = None;
+                    if self.reconnect_modal {
+                        self.lost = Some(info.clone());
+                    }
+                }
+            }
+            GamepadEvent::ActiveChanged(info) => {
+                self.active = Some(info.id);
+                self.lost = None;
+            }
+            _ => (),
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        self.draw_toasts(ui);
+
+        if self.lost.is_some() && !self.modal_open {
+            ui.open_popup(RECONNECT_POPUP);
+            self.modal_open = true;
+        }
+        let shown = ui.modal_popup(RECONNECT_POPUP, || {
+            let Some(lost) = &self.lost else {
+                ui.close_current_popup();
+                return;
+            };
+            ui.text(format!("{} was disconnected.", lost.display_name()));
+            ui.text("Reconnect it, or press a button on another controller.");
+        });
+        if shown.is_none() {
+            self.modal_open = false;
+        }
+    }
+
+    fn push_toast(&mut self, message: String) {
+        self.toasts.push(Toast {
+            message,
+            created: Instant::now(),
+        });
+    }
+
+    fn draw_toasts(&mut self, ui: &Ui) {
+        let duration = seconds(self.toast_duration);
+        self.toasts
+            .retain(|toast| toast.created.elapsed() < duration);
+
+        let [width, height] = ui.io().display_size;
+        let mut bottom = height - TOAST_MARGIN;
+        // Newest toast at the bottom
+        for (index, toast) in self.toasts.iter().rev().enumerate() {
+            ui.window(format!("##gamepad_toast{index}"))
+                .position([width - TOAST_MARGIN, bottom], Condition::Always)
+                .position_pivot([1.0, 1.0])
+                .bg_alpha(TOAST_ALPHA)
+                .flags(
+                    WindowFlags::NO_DECORATION
+                        | WindowFlags::ALWAYS_AUTO_RESIZE
+                        | WindowFlags::NO_INPUTS
+                        | WindowFlags::NO_NAV
+                        | WindowFlags::NO_FOCUS_ON_APPEARING
+                        | WindowFlags::NO_SAVED_SETTINGS,
+                )
+                .build(|| {
+                    ui.text(&toast.message);
+                    bottom -= ui.window_size()[1] + TOAST_SPACING;
+                });
+        }
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Kind of device that produced an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    Gamepad,
+    Mouse,
+    Keyboard,
+}
+
+/// What happens when the gamepad takes over from the mouse, until the mouse is used again.
+///
+/// Disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HybridMode {
+    /// Hides the OS cursor, see [`GamepadHandler::update_mouse_cursor()`]
+    ///
+    /// [`GamepadHandler::update_mouse_cursor()`]: crate::GamepadHandler::update_mouse_cursor
+    pub hide_cursor: bool,
+    /// Moves imgui's mouse off screen, so that hovered items don't compete with the nav highlight
+    pub park_mouse: bool,
+}
@@ -0,0 +1,83 @@
+//! imgui types don't implement serde traits, they're stored by name instead
+
+use std::fmt::Debug;
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+fn serialize_name<T: Debug, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{value:?}"))
+}
+
+fn find_name<T: Debug + Copy, E: de::Error>(variants: &[T], name: &str) -> Result<T, E> {
+    variants
+        .iter()
+        .find(|variant| format!("{variant:?}") == name)
+        .copied()
+        .ok_or_else(|| de::Error::custom(format!("unknown imgui name `{name}`")))
+}
+
+fn deserialize_name<'de, T: Debug + Copy, D: Deserializer<'de>>(
+    variants: &[T],
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    find_name(variants, &name)
+}
+
+/// `Key::VARIANTS` doesn't include the modifiers
+fn key_variants() -> Vec<imgui::Key> {
+    imgui::Key::VARIANTS
+        .into_iter()
+        .chain(crate::MODIFIER_KEYS)
+        .collect()
+}
+
+pub(crate) mod key {
+    use imgui::Key;
+    use serde::{Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(key: &Key, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_name(key, serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Key, D::Error> {
+        super::deserialize_name(&super::key_variants(), deserializer)
+    }
+}
+
+pub(crate) mod keys {
+    use imgui::Key;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(keys: &[Key], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|key| format!("{key:?}")))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Key>, D::Error> {
+        let variants = super::key_variants();
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|name| super::find_name(&variants, name))
+            .collect()
+    }
+}
+
+pub(crate) mod mouse_button {
+    use imgui::MouseButton;
+    use serde::{Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        button: &MouseButton,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize_name(button, serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<MouseButton, D::Error> {
+        super::deserialize_name(&MouseButton::VARIANTS, deserializer)
+    }
+}
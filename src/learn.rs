@@ -0,0 +1,229 @@
+use gilrs::GamepadId;
+use imgui::{Key, Ui};
+
+use crate::{
+    rebind::key_label, DeviceProfile, GamepadConfig, GamepadHandler, GamepadMapping, PhysicalInput,
+};
+
+/// Keys the player is asked for, in order
+const LEARN_KEYS: [Key; 16] = [
+    Key::GamepadDpadUp,
+    Key::GamepadDpadDown,
+    Key::GamepadDpadLeft,
+    Key::GamepadDpadRight,
+    Key::GamepadFaceDown,
+    Key::GamepadFaceRight,
+    Key::GamepadFaceUp,
+    Key::GamepadFaceLeft,
+    Key::GamepadL1,
+    Key::GamepadR1,
+    Key::GamepadStart,
+    Key::GamepadBack,
+    Key::GamepadLStickUp,
+    Key::GamepadLStickDown,
+    Key::GamepadLStickLeft,
+    Key::GamepadLStickRight,
+];
+
+/// Progress through [`LEARN_KEYS`]
+#[derive(Debug)]
+struct LearnSteps {
+    step: usize,
+    mapping: GamepadMapping,
+}
+
+impl LearnSteps {
+    fn new() -> LearnSteps {
+        LearnSteps {
+            step: 0,
+            mapping: GamepadMapping::empty(),
+        }
+    }
+
+    /// Key of the current step, `None` once every step is done
+    fn key(&self) -> Option<Key> {
+        LEARN_KEYS.get(self.step).copied()
+    }
+
+    fn is_done(&self) -> bool {
+        self.key().is_none()
+    }
+
+    /// Binds `input` to the current step's key and moves to the next step
+    fn bind(&mut self, input: PhysicalInput) {
+        if let Some(key) = self.key() {
+            self.mapping.add_binding(key, input);
+            self.step += 1;
+        }
+    }
+
+    fn skip(&mut self) {
+        if !self.is_done() {
+            self.step += 1;
+        }
+    }
+}
+
+#[derive(Debug)]
+enum LearnState {
+    Idle,
+    Learning {
+        steps: LearnSteps,
+        /// Gamepad being learned, known after its first input
+        gamepad: Option<GamepadId>,
+    },
+    Done(String),
+}
+
+/// Guides the player through pressing each nav input once, and creates a
+/// [`DeviceProfile`] for their gamepad.
+///
+/// Inputs gilrs doesn't recognize are bound through their raw code, so this makes
+/// unknown controllers usable. The profile matches the gamepad's UUID, or its vendor and
+/// product IDs if the UUID isn't known.
+///
+/// Must be drawn inside a window, every frame.
+#[derive(Debug)]
+pub struct LearnWidget {
+    state: LearnState,
+}
+
+impl Default for LearnWidget {
+    fn default() -> Self {
+        LearnWidget {
+            state: LearnState::Idle,
+        }
+    }
+}
+
+impl LearnWidget {
+    pub fn new() -> LearnWidget {
+        LearnWidget::default()
+    }
+
+    pub fn is_learning(&self) -> bool {
+        matches!(self.state, LearnState::Learning { .. })
+    }
+
+    pub fn draw(&mut self, ui: &Ui, handler: &mut GamepadHandler) {
+        match &mut self.state {
+            LearnState::Idle => {
+                if ui.button("Learn a controller") {
+                    self.state = LearnState::Learning {
+                        steps: LearnSteps::new(),
+                        gamepad: None,
+                    };
+                    handler.capture_next_input();
+                }
+            }
+            LearnState::Learning { steps, gamepad } => {
+                if let Some((id, input)) = handler.take_captured_gamepad_input() {
+                    if gamepad.is_none_or(|gamepad| gamepad == id) {
+                        *gamepad = Some(id);
+                        steps.bind(input);
+                    }
+                } else if !steps.is_done()
+                    && !handler.is_capturing()
+                    && !handler.is_holding_captured_input()
+                {
+                    // A held stick would be captured again for the next step
+                    handler.capture_next_input();
+                }
+
+                let Some(key) = steps.key() else {
+                    handler.cancel_capture();
+                    let message = match *gamepad {
+                        Some(id) => create_profile(handler, id, steps.mapping.clone()),
+                        None => String::from("No input was learned"),
+                    };
+                    self.state = LearnState::Done(message);
+                    return;
+                };
+
+                ui.text(format!("Step {}/{}", steps.step + 1, LEARN_KEYS.len()));
+                ui.text(format!("Press {}", key_label(key)));
+                if ui.button("Skip") {
+                    steps.skip();
+                }
+                ui.same_line();
+                if ui.button("Cancel") {
+                    handler.cancel_capture();
+                    self.state = LearnState::Idle;
+                }
+            }
+            LearnState::Done(message) => {
+                ui.text(message.as_str());
+                if ui.button("OK") {
+                    self.state = LearnState::Idle;
+                }
+            }
+        }
+    }
+}
+
+/// Returns a message describing the created profile
+fn create_profile(handler: &mut GamepadHandler, id: GamepadId, mapping: GamepadMapping) -> String {
+    let info = handler.gamepad_info(id);
+    let name = format!("{} (learned)", info.display_name());
+    let config = GamepadConfig {
+        mapping,
+        ..handler.config().clone()
+    };
+    let mut profile = DeviceProfile::new(name.clone(), config);
+    // A nil UUID would match every gamepad without metadata
+    if info.uuid != [0; 16] {
+        profile.uuids.push(info.uuid);
+    } else if info.vendor_id.is_some() {
+        profile.vendor_id = info.vendor_id;
+        profile.product_id = info.product_id;
+    } else {
+        profile.name_pattern = Some(info.name.clone());
+    }
+
+    let profiles = handler.profiles_mut();
+    profiles.profiles.retain(|profile| profile.name != name);
+    profiles.add(profile);
+    handler.set_gamepad_profile(id, Some(name.clone()));
+    format!("Created profile \"{name}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_and_bind_every_step() {
+        let mut steps = LearnSteps::new();
+        steps.bind(PhysicalInput::Button(gilrs::Button::DPadUp));
+        for _ in 1..LEARN_KEYS.len() {
+            steps.skip();
+        }
+        assert!(steps.is_done());
+
+        // Skipping and capturing past the last step do nothing
+        steps.skip();
+        steps.bind(PhysicalInput::Button(gilrs::Button::South));
+        assert_eq!(steps.step, LEARN_KEYS.len());
+        assert_eq!(steps.mapping.bindings().count(), 1);
+        assert_eq!(
+            steps
+                .mapping
+                .key(PhysicalInput::Button(gilrs::Button::DPadUp)),
+            Some(Key::GamepadDpadUp)
+        );
+    }
+
+    #[test]
+    fn bind_follows_skips() {
+        let mut steps = LearnSteps::new();
+        steps.skip();
+        steps.bind(PhysicalInput::AxisNegative(gilrs::Axis::LeftStickY));
+        assert_eq!(steps.key(), Some(LEARN_KEYS[2]));
+        assert_eq!(
+            steps
+                .mapping
+                .key(PhysicalInput::AxisNegative(gilrs::Axis::LeftStickY)),
+            Some(Key::GamepadDpadDown)
+        );
+    }
+}
@@ -1,35 +1,213 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime},
+};
 
-use gilrs::GamepadId;
-use imgui::{BackendFlags, Io, Key};
+use chords::ChordStep;
+use filters::FilterPipeline;
+use gestures::GestureState;
+use gilrs::{ev::Code, GamepadId, Gilrs, PowerInfo};
+use haptics::HapticsState;
+use imgui::{BackendFlags, Io, Key, MouseButton, Ui};
+use players::PlayerSlots;
+
+mod arbitration;
+mod battery;
+mod chords;
+mod config;
+mod debounce;
+mod debug;
+mod events;
+mod filters;
+mod gestures;
+mod haptics;
+mod hotplug;
+mod hybrid;
+#[cfg(feature = "serde")]
+mod imgui_serde;
+mod learn;
+mod mapping;
+mod players;
+mod profiles;
+mod rebind;
+mod repeat;
+mod router;
+mod sdl;
+mod sectors;
+mod shortcuts;
+mod smoothing;
+mod tuning;
+mod turbo;
+pub use arbitration::InputArbitration;
+pub use battery::battery_indicator;
+pub use chords::Chord;
+#[cfg(feature = "serde")]
+pub use config::ConfigError;
+pub use config::{GamepadConfig, TriggerAxisRange, CONFIG_VERSION};
+pub use debounce::Debounce;
+pub use events::{GamepadEvent, GamepadInfo};
+pub use filters::{FilterStage, InputFilter};
+pub use gestures::{Gesture, GestureKind, GestureTarget, GestureTimings};
+pub use haptics::{NavFeedback, NavHaptics, Rumble};
+pub use hotplug::HotplugWidget;
+pub use hybrid::{HybridMode, InputDevice};
+pub use learn::LearnWidget;
+pub use mapping::{GamepadMapping, PhysicalInput};
+pub use players::PLAYER_SLOTS;
+pub use profiles::{DeviceProfile, ProfileRegistry};
+pub use rebind::RebindWidget;
+pub use repeat::{NavRepeat, RepeatSource};
+pub use router::GamepadRouter;
+pub use sdl::{ChangedGamepad, SdlMapping, SdlMappingError, SdlMappings};
+pub use sectors::{StickDirections, StickSectors};
+pub use shortcuts::Shortcut;
+pub use smoothing::{Smoothing, StickSmoothing};
+pub use tuning::{AxisTuning, InputTuning, ResponseCurve};
+pub use turbo::Turbo;
 
 #[cfg(feature = "winit")]
 use imgui_winit_support::WinitPlatform;
 #[cfg(feature = "winit")]
 use winit::window::Window;
 
-fn to_imgui_gamepad_key(button: gilrs::Button) -> Option<Key> {
-    match button {
-        gilrs::Button::South => Some(Key::GamepadFaceDown),
-        gilrs::Button::East => Some(Key::GamepadFaceRight),
-        gilrs::Button::North => Some(Key::GamepadFaceUp),
-        gilrs::Button::West => Some(Key::GamepadFaceLeft),
-        gilrs::Button::C => None,
-        gilrs::Button::Z => None,
-        gilrs::Button::LeftTrigger => Some(Key::GamepadL1),
-        gilrs::Button::LeftTrigger2 => Some(Key::GamepadL2),
-        gilrs::Button::RightTrigger => Some(Key::GamepadR1),
-        gilrs::Button::RightTrigger2 => Some(Key::GamepadR1),
-        gilrs::Button::Select => Some(Key::GamepadBack),
-        gilrs::Button::Start => Some(Key::GamepadStart),
-        gilrs::Button::Mode => None,
-        gilrs::Button::LeftThumb => Some(Key::GamepadL3),
-        gilrs::Button::RightThumb => Some(Key::GamepadR3),
-        gilrs::Button::DPadUp => Some(Key::GamepadDpadUp),
-        gilrs::Button::DPadDown => Some(Key::GamepadDpadDown),
-        gilrs::Button::DPadLeft => Some(Key::GamepadDpadLeft),
-        gilrs::Button::DPadRight => Some(Key::GamepadDpadRight),
-        gilrs::Button::Unknown => None,
+/// Every imgui key used for gamepad navigation
+const GAMEPAD_KEYS: [Key; 24] = [
+    Key::GamepadStart,
+    Key::GamepadBack,
+    Key::GamepadFaceLeft,
+    Key::GamepadFaceRight,
+    Key::GamepadFaceUp,
+    Key::GamepadFaceDown,
+    Key::GamepadDpadLeft,
+    Key::GamepadDpadRight,
+    Key::GamepadDpadUp,
+    Key::GamepadDpadDown,
+    Key::GamepadL1,
+    Key::GamepadR1,
+    Key::GamepadL2,
+    Key::GamepadR2,
+    Key::GamepadL3,
+    Key::GamepadR3,
+    Key::GamepadLStickLeft,
+    Key::GamepadLStickRight,
+    Key::GamepadLStickUp,
+    Key::GamepadLStickDown,
+    Key::GamepadRStickLeft,
+    Key::GamepadRStickRight,
+    Key::GamepadRStickUp,
+    Key::GamepadRStickDown,
+];
+
+/// Keys sent by [`GamepadMapping::keyboard_nav()`]
+const KEYBOARD_NAV_KEYS: [Key; 8] = [
+    Key::Space,
+    Key::Enter,
+    Key::Escape,
+    Key::Tab,
+    Key::UpArrow,
+    Key::DownArrow,
+    Key::LeftArrow,
+    Key::RightArrow,
+];
+
+/// Keyboard modifiers that gamepad inputs can hold, see [`GamepadMapping`]
+const MODIFIER_KEYS: [Key; 4] = [Key::ModCtrl, Key::ModShift, Key::ModAlt, Key::ModSuper];
+
+#[derive(Clone, PartialEq, Eq)]
+enum InputOutcome {
+    /// At least one imgui key went down
+    Pressed,
+    Idle,
+    /// The input doesn't correspond to any imgui key
+    Unmapped,
+    /// A gesture clicked this imgui mouse button
+    Clicked(MouseButton),
+    /// The input completed the chord with this action
+    Chord(String),
+    /// The input completed a gesture with this action
+    Gesture(String),
+}
+
+/// Whether the event's input reaches imgui as a key, shortcut or gesture
+fn is_bound(config: &GamepadConfig, event: gilrs::EventType) -> bool {
+    use gilrs::EventType as GEvent;
+    let button_bound = |button, code| {
+        config.mapping.button_key(button, code).is_some()
+            || shortcuts::find_shortcut(config, button).is_some()
+            || gestures::has_gestures(config, button)
+    };
+    match event {
+        GEvent::ButtonPressed(button, code)
+        | GEvent::ButtonReleased(button, code)
+        | GEvent::ButtonRepeated(button, code)
+        | GEvent::ButtonChanged(button, _, code) => button_bound(button, code),
+        GEvent::AxisChanged(axis @ (gilrs::Axis::LeftZ | gilrs::Axis::RightZ), _, _) => {
+            let input = PhysicalInput::AxisPositive(axis);
+            config.mapping.key(input).is_some()
+                || shortcuts::find_shortcut(config, trigger_button(axis)).is_some()
+                || gestures::has_gestures(config, trigger_button(axis))
+        }
+        GEvent::AxisChanged(axis, _, code) => {
+            config.mapping.has_raw_axis(code)
+                || (axis != gilrs::Axis::Unknown
+                    && [
+                        PhysicalInput::AxisNegative(axis),
+                        PhysicalInput::AxisPositive(axis),
+                    ]
+                    .into_iter()
+                    .any(|input| config.mapping.key(input).is_some()))
+        }
+        _ => false,
+    }
+}
+
+/// The button L2/R2 send when they're reported as `Axis::LeftZ`/`Axis::RightZ`
+fn trigger_button(axis: gilrs::Axis) -> gilrs::Button {
+    match axis {
+        gilrs::Axis::LeftZ => gilrs::Button::LeftTrigger2,
+        _ => gilrs::Button::RightTrigger2,
+    }
+}
+
+fn is_trigger(button: gilrs::Button) -> bool {
+    matches!(
+        button,
+        gilrs::Button::LeftTrigger2 | gilrs::Button::RightTrigger2
+    )
+}
+
+/// What a gilrs event does to the buttons of chords
+enum ChordInput {
+    Press(gilrs::Button),
+    Release(gilrs::Button),
+    /// A change that doesn't press or release the button
+    Change(gilrs::Button),
+    Other,
+}
+
+/// Triggers reported as axes press their button when crossing the trigger threshold
+fn chord_input(
+    config: &GamepadConfig,
+    pressed_buttons: &HashSet<gilrs::Button>,
+    event: gilrs::EventType,
+) -> ChordInput {
+    use gilrs::EventType as GEvent;
+    match event {
+        GEvent::ButtonPressed(button, _) => ChordInput::Press(button),
+        GEvent::ButtonReleased(button, _) => ChordInput::Release(button),
+        GEvent::ButtonChanged(button, _, _) | GEvent::ButtonRepeated(button, _) => {
+            ChordInput::Change(button)
+        }
+        GEvent::AxisChanged(axis @ (gilrs::Axis::LeftZ | gilrs::Axis::RightZ), value, _) => {
+            let button = trigger_button(axis);
+            let down = config.trigger_axis_range.normalize(value) > config.trigger_threshold;
+            match (down, pressed_buttons.contains(&button)) {
+                (true, false) => ChordInput::Press(button),
+                (false, true) => ChordInput::Release(button),
+                _ => ChordInput::Change(button),
+            }
+        }
+        _ => ChordInput::Other,
     }
 }
 
@@ -37,6 +215,8 @@ fn to_imgui_gamepad_key(button: gilrs::Button) -> Option<Key> {
 enum AnalogueGamepadInput {
     L2,
     R2,
+    LZ,
+    RZ,
     LUp,
     LDown,
     LLeft,
@@ -55,151 +235,678 @@ enum AnalogueGamepadInput {
 struct GamepadState {
     l2: f32,
     r2: f32,
+    /// Normalized `Axis::LeftZ`
+    lz: f32,
+    /// Normalized `Axis::RightZ`
+    rz: f32,
     lx: f32,
     ly: f32,
     rx: f32,
     ry: f32,
     dpad_x: f32,
     dpad_y: f32,
+    /// Sectors of the sticks, see [`StickDirections`]
+    l_sector: Option<u8>,
+    r_sector: Option<u8>,
+    pressed_buttons: HashSet<gilrs::Button>,
+    /// Held buttons that belong to a recognized chord, imgui must not see them
+    chord_buttons: HashSet<gilrs::Button>,
+    /// Presses that may start a chord, in order
+    held_back: Vec<(gilrs::Button, gilrs::Event)>,
+    /// L2/R2 pressed in imgui, see [`GamepadState::set_trigger()`]
+    triggers_down: HashSet<gilrs::Button>,
+    gestures: HashMap<gilrs::Button, GestureState>,
+    /// Keys held by the [`Shortcut`] of each button
+    held_shortcuts: HashMap<gilrs::Button, Vec<Key>>,
+    /// Values of the axes handled through their raw code
+    raw_axes: HashMap<Code, f32>,
+    /// imgui keys this gamepad pressed and didn't release yet
+    keys_down: HashSet<Key>,
+    /// Name of the [`DeviceProfile`] used by the gamepad, `None` for the default config
+    profile: Option<String>,
 }
 
 impl GamepadState {
-    fn new() -> GamepadState {
+    fn new(profile: Option<String>) -> GamepadState {
         GamepadState {
             l2: 0.0,
             r2: 0.0,
+            lz: 0.0,
+            rz: 0.0,
             lx: 0.0,
             ly: 0.0,
             rx: 0.0,
             ry: 0.0,
             dpad_x: 0.0,
             dpad_y: 0.0,
+            l_sector: None,
+            r_sector: None,
+            pressed_buttons: HashSet::new(),
+            chord_buttons: HashSet::new(),
+            held_back: Vec::new(),
+            triggers_down: HashSet::new(),
+            gestures: HashMap::new(),
+            held_shortcuts: HashMap::new(),
+            raw_axes: HashMap::new(),
+            keys_down: HashSet::new(),
+            profile,
         }
     }
 
-    fn is_triggered(&self, trigger: AnalogueGamepadInput) -> bool {
+    /// Brings every analogue input back to rest, without sending key events
+    fn release_analogue_inputs(&mut self) {
+        self.l2 = 0.0;
+        self.r2 = 0.0;
+        self.lz = 0.0;
+        self.rz = 0.0;
+        self.lx = 0.0;
+        self.ly = 0.0;
+        self.rx = 0.0;
+        self.ry = 0.0;
+        self.dpad_x = 0.0;
+        self.dpad_y = 0.0;
+        self.l_sector = None;
+        self.r_sector = None;
+        self.raw_axes.clear();
+    }
+
+    /// Sends a key event to imgui, keeping track of the keys held
+    fn key_event(&mut self, io: &mut Io, key: Key, down: bool) {
+        if down {
+            self.keys_down.insert(key);
+        } else {
+            self.keys_down.remove(&key);
+        }
+        io.add_key_event(key, down);
+    }
+
+    /// Releases the imgui keys this gamepad holds, and only those
+    fn release_keys(&mut self, io: &mut Io) {
+        for key in self.keys_down.drain() {
+            io.add_key_event(key, false);
+        }
+    }
+
+    /// Presses the key, shortcut or gestures of a button, triggers go through
+    /// [`GamepadState::set_trigger()`]
+    fn press(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        code: Code,
+        time: SystemTime,
+    ) -> InputOutcome {
+        if is_trigger(button) {
+            let input = PhysicalInput::Button(button);
+            return self.set_trigger(io, config, input, code, true, time);
+        }
+        let key = config.mapping.button_key(button, code);
+        self.press_button(io, config, button, key, code, time)
+    }
+
+    fn release(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        code: Code,
+        time: SystemTime,
+    ) -> InputOutcome {
+        if is_trigger(button) {
+            let input = PhysicalInput::Button(button);
+            return self.set_trigger(io, config, input, code, false, time);
+        }
+        let key = config.mapping.button_key(button, code);
+        self.release_button(io, config, button, key, code, time)
+    }
+
+    /// Forgets the held buttons, their chords and gestures, without sending key events
+    fn release_buttons(&mut self) {
+        self.pressed_buttons.clear();
+        self.chord_buttons.clear();
+        self.held_back.clear();
+        self.triggers_down.clear();
+        self.gestures.clear();
+    }
+
+    fn release_shortcut(&mut self, io: &mut Io, button: gilrs::Button) -> bool {
+        let Some(keys) = self.held_shortcuts.remove(&button) else {
+            return false;
+        };
+        for key in keys.into_iter().rev() {
+            self.key_event(io, key, false);
+        }
+        true
+    }
+
+    fn release_shortcuts(&mut self, io: &mut Io) {
+        let buttons: Vec<_> = self.held_shortcuts.keys().copied().collect();
+        for button in buttons {
+            self.release_shortcut(io, button);
+        }
+    }
+
+    fn is_triggered(&self, config: &GamepadConfig, trigger: AnalogueGamepadInput) -> bool {
         use AnalogueGamepadInput as Input;
-        const TRESHOLD: f32 = 0.01;
+        let dead_zone = config.dead_zone;
         match trigger {
-            Input::L2 => self.l2 > TRESHOLD,
-            Input::R2 => self.r2 > TRESHOLD,
-            Input::LUp => self.ly > TRESHOLD,
-            Input::LDown => self.ly < -TRESHOLD,
-            Input::LLeft => self.lx < -TRESHOLD,
-            Input::LRight => self.lx > TRESHOLD,
-            Input::RUp => self.ry > TRESHOLD,
-            Input::RDown => self.ry < -TRESHOLD,
-            Input::RLeft => self.rx < -TRESHOLD,
-            Input::RRight => self.rx > TRESHOLD,
-            Input::DPadUp => self.dpad_y > TRESHOLD,
-            Input::DPadDown => self.dpad_y < -TRESHOLD,
-            Input::DPadLeft => self.dpad_x > TRESHOLD,
-            Input::DPadRight => self.dpad_x < -TRESHOLD,
-        }
-    }
-
-    fn change_analogue_button(&mut self, io: &mut Io, button: gilrs::Button, value: f32) {
-        let (analogue_input, imgui_key) = match button {
-            gilrs::Button::LeftTrigger2 => (
-                AnalogueGamepadInput::L2,
-                to_imgui_gamepad_key(button).unwrap(),
-            ),
-            gilrs::Button::RightTrigger2 => (
-                AnalogueGamepadInput::R2,
-                to_imgui_gamepad_key(button).unwrap(),
-            ),
-            _ => return, // Only supports analogue bottom triggers
+            Input::L2 => self.l2 > config.trigger_threshold,
+            Input::R2 => self.r2 > config.trigger_threshold,
+            Input::LZ => self.lz > config.trigger_threshold,
+            Input::RZ => self.rz > config.trigger_threshold,
+            Input::LUp => self.ly > dead_zone,
+            Input::LDown => self.ly < -dead_zone,
+            Input::LLeft => self.lx < -dead_zone,
+            Input::LRight => self.lx > dead_zone,
+            Input::RUp => self.ry > dead_zone,
+            Input::RDown => self.ry < -dead_zone,
+            Input::RLeft => self.rx < -dead_zone,
+            Input::RRight => self.rx > dead_zone,
+            Input::DPadUp => self.dpad_y > dead_zone,
+            Input::DPadDown => self.dpad_y < -dead_zone,
+            Input::DPadLeft => self.dpad_x < -dead_zone,
+            Input::DPadRight => self.dpad_x > dead_zone,
+        }
+    }
+
+    fn is_held_back(&self, button: gilrs::Button) -> bool {
+        self.held_back.iter().any(|(held, _)| *held == button)
+    }
+
+    /// Recognizes chords, whether imgui or the game gets the inputs.
+    ///
+    /// With `hold_back`, presses that may start a chord wait until it completes or fails.
+    fn track_chords(
+        &mut self,
+        config: &GamepadConfig,
+        event: &gilrs::Event,
+        hold_back: bool,
+    ) -> ChordStep {
+        match chord_input(config, &self.pressed_buttons, event.event) {
+            ChordInput::Press(button) => {
+                self.pressed_buttons.insert(button);
+                let chord = config
+                    .chords
+                    .iter()
+                    .find(|chord| chord.completed_by(button, &self.pressed_buttons));
+                if let Some(chord) = chord {
+                    // The held back presses of the chord never reach imgui
+                    self.held_back.retain(|(held, _)| {
+                        let in_chord = chord.buttons.contains(held);
+                        if in_chord {
+                            self.chord_buttons.insert(*held);
+                        }
+                        !in_chord
+                    });
+                    self.chord_buttons.insert(button);
+                    return ChordStep::Chord(chord.action.clone());
+                }
+                if hold_back
+                    && config
+                        .chords
+                        .iter()
+                        .any(|chord| chord.buttons.contains(&button))
+                {
+                    self.held_back.push((button, *event));
+                    return ChordStep::Swallowed;
+                }
+                ChordStep::Pass(vec![*event])
+            }
+            ChordInput::Release(button) => {
+                self.pressed_buttons.remove(&button);
+                if self.chord_buttons.remove(&button) {
+                    return ChordStep::Swallowed;
+                }
+                if self.is_held_back(button) {
+                    // The chord failed, the presses reach imgui late
+                    let mut events: Vec<_> =
+                        self.held_back.drain(..).map(|(_, event)| event).collect();
+                    events.push(*event);
+                    return ChordStep::Pass(events);
+                }
+                ChordStep::Pass(vec![*event])
+            }
+            ChordInput::Change(button)
+                if self.chord_buttons.contains(&button) || self.is_held_back(button) =>
+            {
+                ChordStep::Swallowed
+            }
+            _ => ChordStep::Pass(vec![*event]),
+        }
+    }
+
+    /// Held back presses whose chord wasn't completed in time
+    fn take_expired_presses(
+        &mut self,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let chord_time = seconds(config.chord_time);
+        let expired = self.held_back.first().is_some_and(|(_, held)| {
+            now.duration_since(held.time).unwrap_or_default() >= chord_time
+        });
+        if expired {
+            self.held_back.drain(..).map(|(_, event)| event).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// `key` is the imgui key the button is mapped to
+    fn press_button(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        key: Option<Key>,
+        code: Code,
+        time: SystemTime,
+    ) -> InputOutcome {
+        if let Some(shortcut) = shortcuts::find_shortcut(config, button) {
+            for &key in &shortcut.keys {
+                self.key_event(io, key, true);
+            }
+            self.held_shortcuts.insert(button, shortcut.keys.clone());
+            return InputOutcome::Pressed;
+        }
+
+        if gestures::has_gestures(config, button) {
+            return self
+                .gestures
+                .entry(button)
+                .or_insert_with(|| GestureState::new(code))
+                .press(io, config, button, code, time);
+        }
+
+        if let Some(key) = key {
+            self.key_event(io, key, true);
+            InputOutcome::Pressed
+        } else {
+            InputOutcome::Unmapped
+        }
+    }
+
+    fn release_button(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        key: Option<Key>,
+        code: Code,
+        time: SystemTime,
+    ) -> InputOutcome {
+        if self.release_shortcut(io, button) {
+            return InputOutcome::Idle;
+        }
+
+        if gestures::has_gestures(config, button) {
+            return self
+                .gestures
+                .entry(button)
+                .or_insert_with(|| GestureState::new(code))
+                .release(io, config, button, time);
+        }
+
+        if let Some(key) = key {
+            self.key_event(io, key, false)
+        }
+        InputOutcome::Idle
+    }
+
+    fn update_gestures(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<InputOutcome> {
+        self.gestures
+            .iter_mut()
+            .map(|(button, gesture)| gesture.update(io, config, *button, now))
+            .filter(|outcome| *outcome != InputOutcome::Idle)
+            .collect()
+    }
+
+    /// Presses or releases L2/R2, whether they're reported as buttons or as axes, so that
+    /// both go through the same shortcuts and gestures.
+    ///
+    /// Analogue triggers are pressed by their `ButtonPressed` event and by their value, the
+    /// first one wins. `input` is the trigger's button, or its `AxisPositive`.
+    fn set_trigger(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        input: PhysicalInput,
+        code: Code,
+        down: bool,
+        time: SystemTime,
+    ) -> InputOutcome {
+        let (trigger, key) = match input {
+            PhysicalInput::AxisPositive(axis) => (trigger_button(axis), config.mapping.key(input)),
+            PhysicalInput::Button(button) => (button, config.mapping.button_key(button, code)),
+            _ => return InputOutcome::Unmapped,
+        };
+        if self.triggers_down.contains(&trigger) == down {
+            return InputOutcome::Idle;
+        }
+        if down {
+            self.triggers_down.insert(trigger);
+            self.press_button(io, config, trigger, key, code, time)
+        } else {
+            self.triggers_down.remove(&trigger);
+            self.release_button(io, config, trigger, key, code, time)
+        }
+    }
+
+    fn change_analogue_button(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        button: gilrs::Button,
+        code: Code,
+        value: f32,
+        time: SystemTime,
+    ) -> InputOutcome {
+        let analogue_input = match button {
+            gilrs::Button::LeftTrigger2 => AnalogueGamepadInput::L2,
+            gilrs::Button::RightTrigger2 => AnalogueGamepadInput::R2,
+            _ => return InputOutcome::Idle, // Only supports analogue bottom triggers
         };
 
-        let was_triggered = self.is_triggered(analogue_input);
+        let was_triggered = self.is_triggered(config, analogue_input);
         // update state
         match analogue_input {
             AnalogueGamepadInput::L2 => self.l2 = value,
             AnalogueGamepadInput::R2 => self.r2 = value,
             _ => unreachable!(),
         }
-        let is_triggered = self.is_triggered(analogue_input);
-        if !was_triggered && is_triggered {
-            io.add_key_event(imgui_key, true)
-        } else if was_triggered && !is_triggered {
-            io.add_key_event(imgui_key, false)
+        let is_triggered = self.is_triggered(config, analogue_input);
+        if was_triggered == is_triggered {
+            return InputOutcome::Idle;
         }
+        let input = PhysicalInput::Button(button);
+        self.set_trigger(io, config, input, code, is_triggered, time)
     }
 
-    fn change_axis(&mut self, io: &mut Io, axis: gilrs::Axis, value: f32) {
-        use AnalogueGamepadInput as Input;
-        let (analogue_input_neg, analogue_input_pos, imgui_key_neg, imgui_key_pos) = match axis {
-            gilrs::Axis::LeftStickX => (
-                Input::LLeft,
-                Input::LRight,
-                Key::GamepadLStickLeft,
-                Key::GamepadLStickRight,
-            ),
-            gilrs::Axis::LeftStickY => (
-                Input::LDown,
-                Input::LUp,
-                Key::GamepadLStickDown,
-                Key::GamepadLStickUp,
-            ),
-            gilrs::Axis::RightStickX => (
-                Input::RLeft,
-                Input::RRight,
-                Key::GamepadRStickLeft,
-                Key::GamepadRStickRight,
+    fn change_trigger_axis(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        axis: gilrs::Axis,
+        code: Code,
+        value: f32,
+        time: SystemTime,
+    ) -> InputOutcome {
+        let analogue_input = match axis {
+            gilrs::Axis::LeftZ => AnalogueGamepadInput::LZ,
+            gilrs::Axis::RightZ => AnalogueGamepadInput::RZ,
+            _ => unreachable!(),
+        };
+
+        let was_triggered = self.is_triggered(config, analogue_input);
+        // update state
+        let value = config.trigger_axis_range.normalize(value);
+        match analogue_input {
+            AnalogueGamepadInput::LZ => self.lz = value,
+            AnalogueGamepadInput::RZ => self.rz = value,
+            _ => unreachable!(),
+        }
+        let is_triggered = self.is_triggered(config, analogue_input);
+        if was_triggered == is_triggered {
+            return InputOutcome::Idle;
+        }
+        let input = PhysicalInput::AxisPositive(axis);
+        self.set_trigger(io, config, input, code, is_triggered, time)
+    }
+
+    /// Axes without a supported name, or with bindings of their raw code
+    fn change_raw_axis(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        code: Code,
+        value: f32,
+    ) -> InputOutcome {
+        let dead_zone = config.dead_zone;
+        let old_value = self.raw_axes.insert(code, value).unwrap_or_default();
+        let mut bound = false;
+        let mut outcome = InputOutcome::Idle;
+        let directions = [
+            (PhysicalInput::RawAxisNegative(code), -old_value, -value),
+            (PhysicalInput::RawAxisPositive(code), old_value, value),
+        ];
+        for (input, old_value, value) in directions {
+            let Some(imgui_key) = config.mapping.key(input) else {
+                continue;
+            };
+            bound = true;
+            let was_triggered = old_value > dead_zone;
+            let is_triggered = value > dead_zone;
+            if !was_triggered && is_triggered {
+                self.key_event(io, imgui_key, true);
+                outcome = InputOutcome::Pressed;
+            } else if was_triggered && !is_triggered {
+                self.key_event(io, imgui_key, false)
+            }
+        }
+
+        // Unbound axes are only reported when pushed, not for every value they send
+        let pushed =
+            old_value.abs() < UNMAPPED_AXIS_THRESHOLD && value.abs() >= UNMAPPED_AXIS_THRESHOLD;
+        if !bound && pushed {
+            return InputOutcome::Unmapped;
+        }
+        outcome
+    }
+
+    /// Presses the directions of the sticks' sectors, see [`StickDirections`]
+    ///
+    /// Called once both axes of a stick changed, so that moving between two diagonals doesn't
+    /// press the direction in between.
+    fn update_sectors(&mut self, io: &mut Io, config: &GamepadConfig) -> InputOutcome {
+        if config.stick_directions == StickDirections::Independent {
+            return InputOutcome::Idle;
+        }
+        let left =
+            self.change_stick_sector(io, config, gilrs::Axis::LeftStickX, gilrs::Axis::LeftStickY);
+        let right = self.change_stick_sector(
+            io,
+            config,
+            gilrs::Axis::RightStickX,
+            gilrs::Axis::RightStickY,
+        );
+        match left {
+            InputOutcome::Pressed => left,
+            _ => right,
+        }
+    }
+
+    fn change_stick_sector(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        x_axis: gilrs::Axis,
+        y_axis: gilrs::Axis,
+    ) -> InputOutcome {
+        let (x, y, sector) = match x_axis {
+            gilrs::Axis::LeftStickX => (self.lx, self.ly, &mut self.l_sector),
+            _ => (self.rx, self.ry, &mut self.r_sector),
+        };
+        let old_direction = sectors::sector_direction(*sector);
+        *sector = config
+            .stick_directions
+            .sector(*sector, x, y, config.dead_zone);
+        let direction = sectors::sector_direction(*sector);
+
+        let inputs = [
+            (
+                PhysicalInput::AxisNegative(x_axis),
+                old_direction.0 < 0,
+                direction.0 < 0,
             ),
-            gilrs::Axis::RightStickY => (
-                Input::RDown,
-                Input::RUp,
-                Key::GamepadRStickDown,
-                Key::GamepadRStickUp,
+            (
+                PhysicalInput::AxisPositive(x_axis),
+                old_direction.0 > 0,
+                direction.0 > 0,
             ),
-            gilrs::Axis::DPadX => (
-                Input::DPadLeft,
-                Input::DPadRight,
-                Key::GamepadDpadLeft,
-                Key::GamepadDpadRight,
+            (
+                PhysicalInput::AxisNegative(y_axis),
+                old_direction.1 < 0,
+                direction.1 < 0,
             ),
-            gilrs::Axis::DPadY => (
-                Input::DPadDown,
-                Input::DPadUp,
-                Key::GamepadDpadDown,
-                Key::GamepadDpadUp,
+            (
+                PhysicalInput::AxisPositive(y_axis),
+                old_direction.1 > 0,
+                direction.1 > 0,
             ),
-            _ => return, // Only supports analogue L/RStick and DPad
+        ];
+        let mut outcome = InputOutcome::Idle;
+        for (input, was_triggered, is_triggered) in inputs {
+            let Some(imgui_key) = config.mapping.key(input) else {
+                continue;
+            };
+            if !was_triggered && is_triggered {
+                self.key_event(io, imgui_key, true);
+                outcome = InputOutcome::Pressed;
+            } else if was_triggered && !is_triggered {
+                self.key_event(io, imgui_key, false)
+            }
+        }
+
+        outcome
+    }
+
+    fn change_axis(
+        &mut self,
+        io: &mut Io,
+        config: &GamepadConfig,
+        axis: gilrs::Axis,
+        code: Code,
+        value: f32,
+        time: SystemTime,
+    ) -> InputOutcome {
+        use AnalogueGamepadInput as Input;
+        if axis == gilrs::Axis::Unknown || config.mapping.has_raw_axis(code) {
+            return self.change_raw_axis(io, config, code, value);
+        }
+        let (analogue_input_neg, analogue_input_pos) = match axis {
+            gilrs::Axis::LeftStickX => (Input::LLeft, Input::LRight),
+            gilrs::Axis::LeftStickY => (Input::LDown, Input::LUp),
+            gilrs::Axis::RightStickX => (Input::RLeft, Input::RRight),
+            gilrs::Axis::RightStickY => (Input::RDown, Input::RUp),
+            gilrs::Axis::DPadX => (Input::DPadLeft, Input::DPadRight),
+            gilrs::Axis::DPadY => (Input::DPadDown, Input::DPadUp),
+            gilrs::Axis::LeftZ | gilrs::Axis::RightZ => {
+                return self.change_trigger_axis(io, config, axis, code, value, time)
+            }
+            // Other axes can only be bound through their raw code
+            _ => return self.change_raw_axis(io, config, code, value),
         };
 
-        let was_triggered_neg = self.is_triggered(analogue_input_neg);
-        let was_triggered_pos = self.is_triggered(analogue_input_pos);
+        let was_triggered_neg = self.is_triggered(config, analogue_input_neg);
+        let was_triggered_pos = self.is_triggered(config, analogue_input_pos);
         // update state
         match analogue_input_neg {
             Input::LDown => self.ly = value,
             Input::LLeft => self.lx = value,
             Input::RDown => self.ry = value,
             Input::RLeft => self.rx = value,
-            Input::DPadDown => self.dpad_x = value,
-            Input::DPadLeft => self.dpad_y = value,
+            Input::DPadLeft => self.dpad_x = value,
+            Input::DPadDown => self.dpad_y = value,
             _ => unreachable!(),
         }
-        let is_triggered_neg = self.is_triggered(analogue_input_neg);
-        let is_triggered_pos = self.is_triggered(analogue_input_pos);
+        let stick = matches!(
+            axis,
+            gilrs::Axis::LeftStickX
+                | gilrs::Axis::LeftStickY
+                | gilrs::Axis::RightStickX
+                | gilrs::Axis::RightStickY
+        );
+        if stick && config.stick_directions != StickDirections::Independent {
+            // See [`GamepadState::update_sectors()`]
+            return InputOutcome::Idle;
+        }
+
+        let is_triggered_neg = self.is_triggered(config, analogue_input_neg);
+        let is_triggered_pos = self.is_triggered(config, analogue_input_pos);
 
-        if !was_triggered_neg && is_triggered_neg {
-            io.add_key_event(imgui_key_neg, true)
-        } else if was_triggered_neg && !is_triggered_neg {
-            io.add_key_event(imgui_key_neg, false)
+        let imgui_key_neg = config.mapping.key(PhysicalInput::AxisNegative(axis));
+        let imgui_key_pos = config.mapping.key(PhysicalInput::AxisPositive(axis));
+
+        let mut outcome = InputOutcome::Idle;
+        if let Some(imgui_key_neg) = imgui_key_neg {
+            if !was_triggered_neg && is_triggered_neg {
+                self.key_event(io, imgui_key_neg, true);
+                outcome = InputOutcome::Pressed;
+            } else if was_triggered_neg && !is_triggered_neg {
+                self.key_event(io, imgui_key_neg, false)
+            }
         }
 
-        if !was_triggered_pos && is_triggered_pos {
-            io.add_key_event(imgui_key_pos, true)
-        } else if was_triggered_pos && !is_triggered_pos {
-            io.add_key_event(imgui_key_pos, false)
+        if let Some(imgui_key_pos) = imgui_key_pos {
+            if !was_triggered_pos && is_triggered_pos {
+                self.key_event(io, imgui_key_pos, true);
+                outcome = InputOutcome::Pressed;
+            } else if was_triggered_pos && !is_triggered_pos {
+                self.key_event(io, imgui_key_pos, false)
+            }
         }
+
+        outcome
     }
 }
 
+/// Duration of a config value in seconds, negative and NaN values count as 0
+pub(crate) fn seconds(value: f32) -> Duration {
+    Duration::try_from_secs_f32(value.max(0.0)).unwrap_or(Duration::MAX)
+}
+
+/// Minimum axis value to capture an axis direction
+const CAPTURE_TRESHOLD: f32 = 0.5;
+
+/// Minimum value of an unbound axis to emit [`GamepadEvent::UnmappedInput`]
+const UNMAPPED_AXIS_THRESHOLD: f32 = 0.5;
+
+/// Events kept until [`GamepadHandler::drain_events()`], older ones are dropped
+const MAX_QUEUED_EVENTS: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+enum InputCapture {
+    /// imgui keys must be released before waiting for an input
+    Starting,
+    Waiting,
+    /// Swallowing the captured input until it's released
+    Releasing(GamepadId, PhysicalInput),
+}
+
 #[derive(Debug)]
 pub struct GamepadHandler {
     connected_controllers: HashMap<GamepadId, GamepadState>,
+    gamepad_info: HashMap<GamepadId, GamepadInfo>,
+    active_gamepad: Option<GamepadId>,
+    events: Vec<GamepadEvent>,
+    profiles: ProfileRegistry,
+    capture: Option<InputCapture>,
+    captured_input: Option<(GamepadId, PhysicalInput)>,
+    haptics: HapticsState,
+    ui_mode: bool,
+    /// Whether imgui got the last gamepad input
+    ui_has_input: bool,
+    /// Gamepads that emitted [`GamepadEvent::LowBattery`] and weren't charged since
+    low_battery: HashSet<GamepadId>,
+    players: PlayerSlots,
+    filters: FilterPipeline,
+    last_input_device: Option<InputDevice>,
+    /// `Io::mouse_pos` on the last [`GamepadHandler::update()`]
+    mouse_pos: [f32; 2],
+    /// `Io::mouse_down` on the last [`GamepadHandler::update()`]
+    mouse_down: [bool; MouseButton::COUNT],
+    /// Mouse buttons clicked by gestures whose press imgui hasn't shown yet
+    gesture_clicks: [bool; MouseButton::COUNT],
+    /// Whether the gamepad took over from the mouse, see [`HybridMode`]
+    gamepad_over_mouse: bool,
+    #[cfg(feature = "winit")]
+    window_cursor_hidden: bool,
 }
 
 impl Default for GamepadHandler {
@@ -212,57 +919,732 @@ impl GamepadHandler {
     pub fn new() -> GamepadHandler {
         GamepadHandler {
             connected_controllers: HashMap::new(),
+            gamepad_info: HashMap::new(),
+            active_gamepad: None,
+            events: Vec::new(),
+            profiles: ProfileRegistry::default(),
+            capture: None,
+            captured_input: None,
+            haptics: HapticsState::default(),
+            ui_mode: false,
+            ui_has_input: true,
+            low_battery: HashSet::new(),
+            players: PlayerSlots::default(),
+            filters: FilterPipeline::default(),
+            last_input_device: None,
+            mouse_pos: [-f32::MAX, -f32::MAX],
+            mouse_down: [false; MouseButton::COUNT],
+            gesture_clicks: [false; MouseButton::COUNT],
+            gamepad_over_mouse: false,
+            #[cfg(feature = "winit")]
+            window_cursor_hidden: false,
         }
     }
 
-    #[cfg(not(feature = "winit"))]
-    #[inline]
-    pub fn handle_event(&mut self, io: &mut Io, controller_event: &gilrs::Event) {
-        self.handle_gilrs_event(io, controller_event)
+    pub fn with_config(config: GamepadConfig) -> GamepadHandler {
+        GamepadHandler::with_profiles(ProfileRegistry::new(config))
+    }
+
+    pub fn with_profiles(profiles: ProfileRegistry) -> GamepadHandler {
+        GamepadHandler {
+            profiles,
+            ..GamepadHandler::new()
+        }
+    }
+
+    /// The config used by gamepads that don't match any [`DeviceProfile`]
+    pub fn config(&self) -> &GamepadConfig {
+        &self.profiles.default
+    }
+
+    pub fn config_mut(&mut self) -> &mut GamepadConfig {
+        &mut self.profiles.default
+    }
+
+    pub fn set_config(&mut self, config: GamepadConfig) {
+        self.profiles.default = config
+    }
+
+    pub fn mapping(&self) -> &GamepadMapping {
+        &self.profiles.default.mapping
+    }
+
+    pub fn mapping_mut(&mut self) -> &mut GamepadMapping {
+        &mut self.profiles.default.mapping
+    }
+
+    pub fn set_mapping(&mut self, mapping: GamepadMapping) {
+        self.profiles.default.mapping = mapping
+    }
+
+    pub fn profiles(&self) -> &ProfileRegistry {
+        &self.profiles
+    }
+
+    /// Changes to the profiles don't affect which profile connected gamepads use,
+    /// see [`GamepadHandler::set_gamepad_profile()`].
+    pub fn profiles_mut(&mut self) -> &mut ProfileRegistry {
+        &mut self.profiles
+    }
+
+    /// Name of the profile used by a connected gamepad, `None` if it uses the default config
+    pub fn gamepad_profile(&self, id: GamepadId) -> Option<&str> {
+        self.connected_controllers
+            .get(&id)
+            .and_then(|gamepad| gamepad.profile.as_deref())
+    }
+
+    pub fn set_gamepad_profile(&mut self, id: GamepadId, profile: Option<String>) {
+        if let Some(gamepad) = self.connected_controllers.get_mut(&id) {
+            gamepad.profile = profile;
+        }
     }
 
-    fn handle_gilrs_event(&mut self, io: &mut Io, controller_event: &gilrs::Event) {
+    /// Config used by a connected gamepad
+    pub fn gamepad_config(&self, id: GamepadId) -> &GamepadConfig {
+        self.profiles.config(self.gamepad_profile(id))
+    }
+
+    /// Config used by the active gamepad, or the default config if there's none
+    pub fn active_config(&self) -> &GamepadConfig {
+        match self.active_gamepad {
+            Some(id) => self.gamepad_config(id),
+            None => &self.profiles.default,
+        }
+    }
+
+    pub fn active_config_mut(&mut self) -> &mut GamepadConfig {
+        let profile = self
+            .active_gamepad
+            .and_then(|id| self.connected_controllers.get(&id))
+            .and_then(|gamepad| gamepad.profile.as_deref());
+        self.profiles.config_mut(profile)
+    }
+
+    fn select_profile(&self, id: GamepadId) -> Option<String> {
+        self.profiles
+            .select(&self.gamepad_info(id))
+            .map(|profile| profile.name.clone())
+    }
+
+    /// Stops sending gamepad inputs to imgui until a button is pressed or an axis is pushed.
+    ///
+    /// Retrieve that input with [`GamepadHandler::take_captured_input()`].
+    pub fn capture_next_input(&mut self) {
+        self.capture = Some(InputCapture::Starting);
+        self.captured_input = None;
+    }
+
+    pub fn cancel_capture(&mut self) {
+        if let Some(InputCapture::Starting | InputCapture::Waiting) = self.capture {
+            self.capture = None;
+        }
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        matches!(
+            self.capture,
+            Some(InputCapture::Starting | InputCapture::Waiting)
+        )
+    }
+
+    /// Whether the captured input is still held. Its events are swallowed until it's released,
+    /// so capture the next input once this is `false`.
+    pub fn is_holding_captured_input(&self) -> bool {
+        matches!(self.capture, Some(InputCapture::Releasing(..)))
+    }
+
+    pub fn take_captured_input(&mut self) -> Option<PhysicalInput> {
+        self.take_captured_gamepad_input().map(|(_, input)| input)
+    }
+
+    /// The captured input along with the gamepad that sent it
+    pub fn take_captured_gamepad_input(&mut self) -> Option<(GamepadId, PhysicalInput)> {
+        self.captured_input.take()
+    }
+
+    /// Returns `true` if the event was swallowed by the input capture
+    fn capture_event(&mut self, io: &mut Io, controller_event: &gilrs::Event) -> bool {
         use gilrs::EventType as GEvent;
-        match controller_event.event {
-            GEvent::ButtonPressed(button, _) => {
-                if let Some(key) = to_imgui_gamepad_key(button) {
-                    io.add_key_event(key, true)
+        let Some(capture) = self.capture else {
+            return false;
+        };
+
+        if let GEvent::Connected | GEvent::Disconnected = controller_event.event {
+            // A disconnected gamepad never releases its captured input
+            if let InputCapture::Releasing(id, _) = capture {
+                if id == controller_event.id && controller_event.event == GEvent::Disconnected {
+                    self.capture = None;
+                }
+            }
+            return false;
+        }
+
+        // Triggers only have a positive direction
+        let event = match controller_event.event {
+            GEvent::AxisChanged(axis @ (gilrs::Axis::LeftZ | gilrs::Axis::RightZ), value, code) => {
+                let range = self.gamepad_config(controller_event.id).trigger_axis_range;
+                GEvent::AxisChanged(axis, range.normalize(value), code)
+            }
+            event => event,
+        };
+
+        match capture {
+            InputCapture::Starting => {
+                self.release_ui_inputs(io);
+                self.capture = Some(InputCapture::Waiting);
+                self.capture_event(io, controller_event)
+            }
+            InputCapture::Waiting => {
+                let input = match event {
+                    GEvent::ButtonPressed(button, code) => {
+                        Some(PhysicalInput::from_button(button, code))
+                    }
+                    GEvent::AxisChanged(axis, value, code) if value >= CAPTURE_TRESHOLD => {
+                        Some(PhysicalInput::from_axis(axis, code, true))
+                    }
+                    GEvent::AxisChanged(axis, value, code) if value <= -CAPTURE_TRESHOLD => {
+                        Some(PhysicalInput::from_axis(axis, code, false))
+                    }
+                    _ => None,
+                };
+                if let Some(input) = input {
+                    self.captured_input = Some((controller_event.id, input));
+                    self.capture = Some(InputCapture::Releasing(controller_event.id, input));
+                }
+                true
+            }
+            InputCapture::Releasing(id, input) => {
+                if id != controller_event.id {
+                    return false;
+                }
+                match event {
+                    GEvent::ButtonReleased(button, code)
+                        if PhysicalInput::from_button(button, code) == input =>
+                    {
+                        self.capture = None;
+                        true
+                    }
+                    GEvent::ButtonChanged(button, _, code) => {
+                        PhysicalInput::from_button(button, code) == input
+                    }
+                    GEvent::AxisChanged(axis, value, code)
+                        if PhysicalInput::from_axis(axis, code, true) == input
+                            || PhysicalInput::from_axis(axis, code, false) == input =>
+                    {
+                        if value.abs() < CAPTURE_TRESHOLD {
+                            self.capture = None;
+                        }
+                        true
+                    }
+                    _ => false,
                 }
             }
-            GEvent::ButtonReleased(button, _) => {
-                if let Some(key) = to_imgui_gamepad_key(button) {
-                    io.add_key_event(key, false)
+        }
+    }
+
+    /// Registers the metadata of every gamepad connected to `gilrs`.
+    ///
+    /// Call it after polling gilrs so that [`GamepadEvent`]s carry the gamepad's metadata.
+    pub fn update_gamepad_info(&mut self, gilrs: &Gilrs) {
+        for (_, gamepad) in gilrs.gamepads() {
+            self.set_gamepad_info(GamepadInfo::from(gamepad));
+        }
+    }
+
+    /// Registers the metadata of a single gamepad.
+    ///
+    /// Useful when gilrs lives on a different thread than the handler.
+    ///
+    /// If the gamepad is already connected and its metadata changed, its profile is selected
+    /// again. Gamepads connected before their metadata is known use the default config until
+    /// then, so set it before handling the `Connected` event when possible.
+    pub fn set_gamepad_info(&mut self, info: GamepadInfo) {
+        let id = info.id;
+        let changed = self
+            .gamepad_info
+            .insert(id, info.clone())
+            .is_none_or(|old| !old.same_device(&info));
+        if !self.connected_controllers.contains_key(&id) {
+            return;
+        }
+        if changed {
+            let profile = self.select_profile(id);
+            if profile.as_deref() != self.gamepad_profile(id) {
+                // The filters hold state made with the old config
+                self.filters.reset(id);
+                self.set_gamepad_profile(id, profile);
+            }
+            let slot = self.players.update_uuid(id, info.uuid);
+            self.push_player_joined(id, slot);
+        }
+        self.check_battery(info);
+    }
+
+    fn check_battery(&mut self, info: GamepadInfo) {
+        let level = self.gamepad_config(info.id).low_battery_level;
+        match info.power {
+            PowerInfo::Discharging(charge) if charge < level => {
+                if self.low_battery.insert(info.id) {
+                    self.push_event(GamepadEvent::LowBattery(info));
                 }
             }
+            // The power state may be unknown for a while
+            PowerInfo::Unknown => (),
+            _ => {
+                self.low_battery.remove(&info.id);
+            }
+        }
+    }
+
+    pub fn gamepad_info(&self, id: GamepadId) -> GamepadInfo {
+        self.gamepad_info
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| GamepadInfo::unknown(id))
+    }
+
+    /// The gamepad that last pressed an imgui key
+    pub fn active_gamepad(&self) -> Option<GamepadId> {
+        self.active_gamepad
+    }
+
+    /// Takes the queued events. At most 256 are kept, older ones are dropped
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GamepadEvent> {
+        self.events.drain(..)
+    }
+
+    pub(crate) fn push_event(&mut self, event: GamepadEvent) {
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
+
+    fn set_active_gamepad(&mut self, id: GamepadId) {
+        if self.active_gamepad != Some(id) {
+            self.active_gamepad = Some(id);
+            let info = self.gamepad_info(id);
+            self.push_event(GamepadEvent::ActiveChanged(info));
+        }
+    }
+
+    /// `event` is the gilrs event that caused the outcome, if any
+    fn handle_outcome(
+        &mut self,
+        io: &mut Io,
+        id: GamepadId,
+        event: Option<gilrs::EventType>,
+        outcome: InputOutcome,
+    ) {
+        if let InputOutcome::Pressed
+        | InputOutcome::Clicked(_)
+        | InputOutcome::Chord(_)
+        | InputOutcome::Gesture(_) = outcome
+        {
+            self.set_active_gamepad(id);
+            self.set_last_input_device(io, InputDevice::Gamepad);
+        }
+        match outcome {
+            InputOutcome::Pressed => (),
+            InputOutcome::Clicked(button) => self.gesture_clicks[button as usize] = true,
+            InputOutcome::Chord(action) => {
+                let info = self.gamepad_info(id);
+                self.push_event(GamepadEvent::Chord(info, action));
+            }
+            InputOutcome::Gesture(action) => {
+                let info = self.gamepad_info(id);
+                self.push_event(GamepadEvent::Gesture(info, action));
+            }
+            InputOutcome::Idle => (),
+            InputOutcome::Unmapped => {
+                if let Some(event) = event {
+                    let info = self.gamepad_info(id);
+                    self.push_event(GamepadEvent::UnmappedInput(info, event));
+                }
+            }
+        }
+    }
+
+    /// Recognizes time-based gestures, such as long presses, updates the [`InputFilter`]s, and
+    /// notices mouse use. Call it once per frame.
+    pub fn update(&mut self, io: &mut Io) {
+        self.detect_mouse(io);
+
+        let now = SystemTime::now();
+        let mut settled = Vec::new();
+        for (id, gamepad) in &self.connected_controllers {
+            let config = self.profiles.config(gamepad.profile.as_deref());
+            settled.extend(self.filters.update_debounce(*id, config, now));
+        }
+        for event in settled {
+            self.handle_input(io, &event);
+        }
+
+        if !self.ui_has_input {
+            return;
+        }
+        let mut outcomes = Vec::new();
+        for (id, gamepad) in &mut self.connected_controllers {
+            let config = self.profiles.config(gamepad.profile.as_deref());
+            for outcome in gamepad.update_gestures(io, config, now) {
+                outcomes.push((*id, outcome));
+            }
+        }
+
+        for (id, outcome) in outcomes {
+            self.handle_outcome(io, id, None, outcome);
+        }
+
+        let mut expired = Vec::new();
+        for gamepad in self.connected_controllers.values_mut() {
+            let config = self.profiles.config(gamepad.profile.as_deref());
+            expired.extend(gamepad.take_expired_presses(config, now));
+        }
+        for event in expired {
+            self.filter_and_send(io, event);
+        }
+
+        let mut filtered = Vec::new();
+        for (id, gamepad) in &self.connected_controllers {
+            let config = self.profiles.config(gamepad.profile.as_deref());
+            filtered.extend(self.filters.update(*id, config, now));
+        }
+        self.send_events(io, &filtered, false);
+    }
+
+    /// Adds a filter that configs can list as [`FilterStage::Custom`] with the same name
+    pub fn add_filter(&mut self, name: impl Into<String>, filter: impl InputFilter + 'static) {
+        self.filters.add(name.into(), Box::new(filter));
+    }
+
+    pub fn remove_filter(&mut self, name: &str) -> Option<Box<dyn InputFilter>> {
+        self.filters.remove(name)
+    }
+
+    /// The kind of device that produced the last input.
+    ///
+    /// Keyboard input is only seen with the `winit` feature, other backends can report it with
+    /// [`GamepadHandler::set_last_input_device()`].
+    pub fn last_input_device(&self) -> Option<InputDevice> {
+        self.last_input_device
+    }
+
+    pub fn set_last_input_device(&mut self, io: &mut Io, device: InputDevice) {
+        self.last_input_device = Some(device);
+        match device {
+            InputDevice::Gamepad if !self.gamepad_over_mouse => {
+                self.gamepad_over_mouse = true;
+                if self.active_config().hybrid.park_mouse {
+                    io.add_mouse_pos_event([-f32::MAX, -f32::MAX]);
+                }
+            }
+            InputDevice::Mouse => self.gamepad_over_mouse = false,
+            _ => (),
+        }
+    }
+
+    fn detect_mouse(&mut self, io: &mut Io) {
+        // Parking the mouse makes its position invalid, that isn't a mouse move
+        let moved = io.mouse_pos != self.mouse_pos && io.mouse_pos[0] > -f32::MAX;
+        self.mouse_pos = io.mouse_pos;
+        // Presses clicked by gestures aren't mouse use
+        let mut clicked = false;
+        for (index, &down) in io.mouse_down.iter().enumerate() {
+            if down && !self.mouse_down[index] {
+                clicked |= !std::mem::take(&mut self.gesture_clicks[index]);
+            }
+        }
+        self.mouse_down = io.mouse_down;
+        let used = moved || clicked || io.mouse_wheel != 0.0 || io.mouse_wheel_h != 0.0;
+        if used {
+            self.set_last_input_device(io, InputDevice::Mouse);
+        }
+    }
+
+    fn hides_cursor(&self) -> bool {
+        self.gamepad_over_mouse && self.active_config().hybrid.hide_cursor
+    }
+
+    /// Hides imgui's mouse cursor while [`HybridMode::hide_cursor`] applies, platform backends
+    /// then hide the OS cursor. Call it once per frame, after drawing the UI.
+    ///
+    /// Not needed with the `winit` feature, which hides the cursor on the `Window`.
+    pub fn update_mouse_cursor(&self, ui: &Ui) {
+        if self.hides_cursor() {
+            ui.set_mouse_cursor(None);
+        }
+    }
+
+    /// Call it after drawing a navigable item, so that nav haptics can tell whether
+    /// the nav focus moved or hit a boundary.
+    ///
+    /// Without tracked items every nav move is reported as [`NavFeedback::FocusMoved`].
+    pub fn track_nav_focus(&mut self, ui: &Ui) {
+        self.haptics.track_item(ui);
+    }
+
+    /// Plays the [`NavHaptics`] of the active gamepad's config on the active gamepad.
+    ///
+    /// Call it once per frame, after drawing the UI.
+    pub fn update_nav_haptics(&mut self, ui: &Ui, gilrs: &mut Gilrs) {
+        let profile = self
+            .active_gamepad
+            .and_then(|id| self.connected_controllers.get(&id))
+            .and_then(|gamepad| gamepad.profile.as_deref());
+        let mapping = &self.profiles.config(profile).mapping;
+        let feedback = self.haptics.update(ui, mapping);
+        let Some(id) = self.active_gamepad else {
+            return;
+        };
+        let Some(haptics) = self.gamepad_config(id).nav_haptics else {
+            return;
+        };
+        for feedback in feedback {
+            if let Some(rumble) = haptics.rumble(feedback) {
+                self.haptics.play(gilrs, id, rumble);
+            }
+        }
+    }
+
+    /// Plays a rumble on a gamepad, replacing the one it's playing
+    pub fn rumble(&mut self, gilrs: &mut Gilrs, id: GamepadId, rumble: Rumble) {
+        self.haptics.play(gilrs, id, rumble);
+    }
+
+    /// Releases the imgui keys held by the gamepads, and forgets their held inputs so that
+    /// releasing them later sends nothing
+    fn release_ui_inputs(&mut self, io: &mut Io) {
+        for (id, gamepad) in &mut self.connected_controllers {
+            gamepad.release_analogue_inputs();
+            gamepad.release_shortcuts(io);
+            gamepad.release_keys(io);
+            gamepad.release_buttons();
+            self.filters.reset(*id);
+        }
+    }
+
+    /// In UI mode imgui gets every gamepad input, see [`InputArbitration`]
+    pub fn ui_mode(&self) -> bool {
+        self.ui_mode
+    }
+
+    pub fn set_ui_mode(&mut self, ui_mode: bool) {
+        self.ui_mode = ui_mode;
+    }
+
+    /// Returns `true` if the event pressed or released the UI mode toggle
+    fn toggle_ui_mode(&mut self, controller_event: &gilrs::Event) -> bool {
+        use gilrs::EventType as GEvent;
+        let toggle = self
+            .gamepad_config(controller_event.id)
+            .arbitration
+            .ui_mode_toggle;
+        match controller_event.event {
+            GEvent::ButtonPressed(button, _) if Some(button) == toggle => {
+                self.ui_mode = !self.ui_mode;
+                true
+            }
+            GEvent::ButtonReleased(button, _)
+            | GEvent::ButtonChanged(button, _, _)
+            | GEvent::ButtonRepeated(button, _) => Some(button) == toggle,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the event was consumed by imgui, and must not reach the game.
+    ///
+    /// Connection events, and inputs that aren't bound to any imgui input, are never consumed.
+    #[cfg(not(feature = "winit"))]
+    #[inline]
+    pub fn handle_event(&mut self, io: &mut Io, controller_event: &gilrs::Event) -> bool {
+        self.handle_gilrs_event(io, controller_event)
+    }
+
+    fn handle_gilrs_event(&mut self, io: &mut Io, controller_event: &gilrs::Event) -> bool {
+        use gilrs::EventType as GEvent;
+        if self.capture_event(io, controller_event) {
+            return true;
+        }
+
+        match controller_event.event {
             GEvent::Connected => {
+                let profile = self.select_profile(controller_event.id);
                 self.connected_controllers
-                    .insert(controller_event.id, GamepadState::new());
+                    .insert(controller_event.id, GamepadState::new(profile));
                 io.backend_flags.insert(BackendFlags::HAS_GAMEPAD);
+                let info = self.gamepad_info(controller_event.id);
+                let slot = self.players.connect(info.id, info.uuid);
+                self.push_event(GamepadEvent::Connected(info));
+                self.push_player_joined(controller_event.id, slot);
+                return false;
             }
             GEvent::Disconnected => {
                 self.connected_controllers.remove(&controller_event.id);
+                self.haptics.stop(controller_event.id);
+                self.low_battery.remove(&controller_event.id);
+                self.players.disconnect(controller_event.id);
+                self.filters.reset(controller_event.id);
                 if self.connected_controllers.is_empty() {
                     // No connected gamepads remain
                     io.backend_flags.remove(BackendFlags::HAS_GAMEPAD);
                 }
+                if self.active_gamepad == Some(controller_event.id) {
+                    self.active_gamepad = None;
+                }
+                let info = self.gamepad_info(controller_event.id);
+                self.push_event(GamepadEvent::Disconnected(info));
+                return false;
+            }
+            GEvent::ForceFeedbackEffectCompleted => {
+                self.haptics.completed(controller_event.id);
+                return false;
+            }
+            GEvent::Dropped => return false,
+            _ => (),
+        }
+
+        // Chattering buttons must not fire chords or toggles twice
+        let profile = self
+            .connected_controllers
+            .get(&controller_event.id)
+            .and_then(|gamepad| gamepad.profile.as_deref());
+        let config = self.profiles.config(profile);
+        let events = self.filters.debounce(config, *controller_event);
+        let mut consumed = false;
+        for event in events {
+            consumed |= self.handle_input(io, &event);
+        }
+        consumed
+    }
+
+    /// Handles a debounced input event, returns `true` if it was consumed
+    fn handle_input(&mut self, io: &mut Io, controller_event: &gilrs::Event) -> bool {
+        let uuid = self.gamepad_info(controller_event.id).uuid;
+        let (joined, slot) = self.players.join(controller_event, uuid);
+        self.push_player_joined(controller_event.id, slot);
+        if joined {
+            return true;
+        }
+
+        if self.toggle_ui_mode(controller_event) {
+            return true;
+        }
+
+        let ui_wants_input = self
+            .gamepad_config(controller_event.id)
+            .arbitration
+            .ui_wants_input(io, self.ui_mode);
+        let events = match self.track_chords(controller_event, ui_wants_input) {
+            ChordStep::Chord(action) => {
+                let outcome = InputOutcome::Chord(action);
+                self.handle_outcome(io, controller_event.id, None, outcome);
+                return true;
+            }
+            ChordStep::Swallowed => return true,
+            ChordStep::Pass(events) => events,
+        };
+        if !ui_wants_input {
+            if self.ui_has_input {
+                // The game takes over, inputs held in imgui would get stuck
+                self.release_ui_inputs(io);
+                self.ui_has_input = false;
+            }
+            return false;
+        }
+        self.ui_has_input = true;
+
+        let mut consumed = false;
+        for event in events {
+            consumed |= self.filter_and_send(io, event);
+        }
+        consumed
+    }
+
+    fn track_chords(&mut self, controller_event: &gilrs::Event, hold_back: bool) -> ChordStep {
+        let Some(gamepad) = self.connected_controllers.get_mut(&controller_event.id) else {
+            return ChordStep::Pass(vec![*controller_event]);
+        };
+        let config = self.profiles.config(gamepad.profile.as_deref());
+        gamepad.track_chords(config, controller_event, hold_back)
+    }
+
+    /// Sends a gilrs event through the filters, then to imgui
+    ///
+    /// Returns `true` if any of the filtered events is bound to imgui inputs.
+    fn filter_and_send(&mut self, io: &mut Io, controller_event: gilrs::Event) -> bool {
+        let profile = self
+            .connected_controllers
+            .get(&controller_event.id)
+            .and_then(|gamepad| gamepad.profile.as_deref());
+        let config = self.profiles.config(profile);
+        let events = self.filters.run(config, controller_event);
+        let bound = events.iter().any(|event| is_bound(config, event.event));
+        self.send_events(io, &events, true);
+        bound
+    }
+
+    /// Sends filtered events to imgui, then updates the sectors of the sticks they moved
+    fn send_events(&mut self, io: &mut Io, events: &[gilrs::Event], from_gilrs: bool) {
+        for event in events {
+            self.send_to_ui(io, event, from_gilrs);
+        }
+        let moved: HashSet<GamepadId> = events
+            .iter()
+            .filter(|event| matches!(event.event, gilrs::EventType::AxisChanged(..)))
+            .map(|event| event.id)
+            .collect();
+        for id in moved {
+            if let Some(gamepad) = self.connected_controllers.get_mut(&id) {
+                let config = self.profiles.config(gamepad.profile.as_deref());
+                let outcome = gamepad.update_sectors(io, config);
+                self.handle_outcome(io, id, None, outcome);
+            }
+        }
+    }
+
+    /// Sends a filtered event to imgui. `from_gilrs` is `false` for events added by the filters
+    fn send_to_ui(&mut self, io: &mut Io, controller_event: &gilrs::Event, from_gilrs: bool) {
+        use gilrs::EventType as GEvent;
+        let source = from_gilrs.then_some(controller_event.event);
+        match controller_event.event {
+            GEvent::ButtonPressed(button, code) => {
+                if let Some(gamepad) = self.connected_controllers.get_mut(&controller_event.id) {
+                    let config = self.profiles.config(gamepad.profile.as_deref());
+                    let outcome = gamepad.press(io, config, button, code, controller_event.time);
+                    self.handle_outcome(io, controller_event.id, source, outcome);
+                }
+            }
+            GEvent::ButtonReleased(button, code) => {
+                if let Some(gamepad) = self.connected_controllers.get_mut(&controller_event.id) {
+                    let config = self.profiles.config(gamepad.profile.as_deref());
+                    let outcome = gamepad.release(io, config, button, code, controller_event.time);
+                    self.handle_outcome(io, controller_event.id, source, outcome);
+                }
             }
-            GEvent::ButtonChanged(button, value, _) => {
+            GEvent::ButtonChanged(button, value, code) => {
                 if let Some(gamepad) = self.connected_controllers.get_mut(&controller_event.id) {
-                    gamepad.change_analogue_button(io, button, value);
+                    let config = self.profiles.config(gamepad.profile.as_deref());
+                    let time = controller_event.time;
+                    let outcome =
+                        gamepad.change_analogue_button(io, config, button, code, value, time);
+                    self.handle_outcome(io, controller_event.id, source, outcome);
                 }
             }
-            GEvent::AxisChanged(axis, value, _) => {
+            GEvent::AxisChanged(axis, value, code) => {
                 if let Some(gamepad) = self.connected_controllers.get_mut(&controller_event.id) {
-                    gamepad.change_axis(io, axis, value);
+                    let config = self.profiles.config(gamepad.profile.as_deref());
+                    let time = controller_event.time;
+                    let outcome = gamepad.change_axis(io, config, axis, code, value, time);
+                    self.handle_outcome(io, controller_event.id, source, outcome);
                 }
             }
-            GEvent::ButtonRepeated(_, _) => (),
-            GEvent::Dropped => (),
-            GEvent::ForceFeedbackEffectCompleted => (),
-            _ => unimplemented!(),
+            // Connection events never reach the filters, but custom filters may add them
+            _ => (),
         }
     }
 
+    /// Returns `true` if the event is a gilrs event consumed by imgui, and must not reach the game.
     #[cfg(feature = "winit")]
     pub fn handle_event(
         &mut self,
@@ -270,12 +1652,36 @@ impl GamepadHandler {
         window: &Window,
         platform: &mut WinitPlatform,
         event: &winit::event::Event<gilrs::Event>,
-    ) {
-        match event {
+    ) -> bool {
+        use winit::event::WindowEvent;
+        let consumed = match event {
             winit::event::Event::UserEvent(controller_event) => {
                 self.handle_gilrs_event(io, controller_event)
             }
-            _ => platform.handle_event(io, window, event),
+            _ => {
+                if let winit::event::Event::WindowEvent { event, .. } = event {
+                    match event {
+                        WindowEvent::CursorMoved { .. }
+                        | WindowEvent::MouseInput { .. }
+                        | WindowEvent::MouseWheel { .. } => {
+                            self.set_last_input_device(io, InputDevice::Mouse)
+                        }
+                        WindowEvent::KeyboardInput { .. } => {
+                            self.set_last_input_device(io, InputDevice::Keyboard)
+                        }
+                        _ => (),
+                    }
+                }
+                platform.handle_event(io, window, event);
+                false
+            }
+        };
+
+        let hide_cursor = self.hides_cursor();
+        if hide_cursor != self.window_cursor_hidden {
+            window.set_cursor_visible(!hide_cursor);
+            self.window_cursor_hidden = hide_cursor;
         }
+        consumed
     }
 }
@@ -0,0 +1,268 @@
+use std::{collections::HashMap, fmt};
+
+use gilrs::ev::Code;
+use imgui::Key;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A physical input of the gamepad that can be bound to an imgui key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PhysicalInput {
+    Button(gilrs::Button),
+    /// Axis pushed towards its positive end
+    AxisPositive(gilrs::Axis),
+    /// Axis pushed towards its negative end
+    AxisNegative(gilrs::Axis),
+    /// Button identified by its platform-specific code, for buttons gilrs doesn't recognize
+    RawButton(Code),
+    RawAxisPositive(Code),
+    RawAxisNegative(Code),
+}
+
+impl PhysicalInput {
+    /// Falls back to the raw code for `Button::Unknown`
+    pub fn from_button(button: gilrs::Button, code: Code) -> PhysicalInput {
+        match button {
+            gilrs::Button::Unknown => PhysicalInput::RawButton(code),
+            button => PhysicalInput::Button(button),
+        }
+    }
+
+    /// Falls back to the raw code for `Axis::Unknown`
+    pub fn from_axis(axis: gilrs::Axis, code: Code, positive: bool) -> PhysicalInput {
+        match (axis, positive) {
+            (gilrs::Axis::Unknown, true) => PhysicalInput::RawAxisPositive(code),
+            (gilrs::Axis::Unknown, false) => PhysicalInput::RawAxisNegative(code),
+            (axis, true) => PhysicalInput::AxisPositive(axis),
+            (axis, false) => PhysicalInput::AxisNegative(axis),
+        }
+    }
+}
+
+impl fmt::Display for PhysicalInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicalInput::Button(button) => write!(f, "{button:?}"),
+            PhysicalInput::AxisPositive(axis) => write!(f, "{axis:?}+"),
+            PhysicalInput::AxisNegative(axis) => write!(f, "{axis:?}-"),
+            PhysicalInput::RawButton(code) => write!(f, "Button {code}"),
+            PhysicalInput::RawAxisPositive(code) => write!(f, "Axis {code}+"),
+            PhysicalInput::RawAxisNegative(code) => write!(f, "Axis {code}-"),
+        }
+    }
+}
+
+/// Which imgui key each physical input sends.
+///
+/// A physical input sends at most one key, but a key can be sent by several inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<serde_impl::Binding>", into = "Vec<serde_impl::Binding>")
+)]
+pub struct GamepadMapping {
+    bindings: HashMap<PhysicalInput, Key>,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        use gilrs::{Axis, Button};
+        use PhysicalInput::{AxisNegative, AxisPositive};
+
+        let bindings = [
+            (Button::South, Key::GamepadFaceDown),
+            (Button::East, Key::GamepadFaceRight),
+            (Button::North, Key::GamepadFaceUp),
+            (Button::West, Key::GamepadFaceLeft),
+            (Button::LeftTrigger, Key::GamepadL1),
+            (Button::LeftTrigger2, Key::GamepadL2),
+            (Button::RightTrigger, Key::GamepadR1),
+            (Button::RightTrigger2, Key::GamepadR2),
+            (Button::Select, Key::GamepadBack),
+            (Button::Start, Key::GamepadStart),
+            (Button::LeftThumb, Key::GamepadL3),
+            (Button::RightThumb, Key::GamepadR3),
+            (Button::DPadUp, Key::GamepadDpadUp),
+            (Button::DPadDown, Key::GamepadDpadDown),
+            (Button::DPadLeft, Key::GamepadDpadLeft),
+            (Button::DPadRight, Key::GamepadDpadRight),
+        ]
+        .into_iter()
+        .map(|(button, key)| (PhysicalInput::Button(button), key))
+        .chain([
+            (AxisNegative(Axis::LeftStickX), Key::GamepadLStickLeft),
+            (AxisPositive(Axis::LeftStickX), Key::GamepadLStickRight),
+            (AxisNegative(Axis::LeftStickY), Key::GamepadLStickDown),
+            (AxisPositive(Axis::LeftStickY), Key::GamepadLStickUp),
+            (AxisNegative(Axis::RightStickX), Key::GamepadRStickLeft),
+            (AxisPositive(Axis::RightStickX), Key::GamepadRStickRight),
+            (AxisNegative(Axis::RightStickY), Key::GamepadRStickDown),
+            (AxisPositive(Axis::RightStickY), Key::GamepadRStickUp),
+            (AxisNegative(Axis::DPadX), Key::GamepadDpadLeft),
+            (AxisPositive(Axis::DPadX), Key::GamepadDpadRight),
+            (AxisNegative(Axis::DPadY), Key::GamepadDpadDown),
+            (AxisPositive(Axis::DPadY), Key::GamepadDpadUp),
+            (AxisPositive(Axis::LeftZ), Key::GamepadL2),
+            (AxisPositive(Axis::RightZ), Key::GamepadR2),
+        ])
+        .collect();
+
+        GamepadMapping { bindings }
+    }
+}
+
+impl GamepadMapping {
+    /// Sends keyboard nav keys instead of gamepad keys, for apps that only enable
+    /// `ConfigFlags::NAV_ENABLE_KEYBOARD`.
+    ///
+    /// D-pad and sticks send arrows, South sends Space (activate), North sends Enter
+    /// (text input), East sends Escape and RB sends Tab. See [`GamepadConfig::keyboard_nav()`]
+    /// for Shift+Tab.
+    ///
+    /// [`GamepadConfig::keyboard_nav()`]: crate::GamepadConfig::keyboard_nav
+    pub fn keyboard_nav() -> GamepadMapping {
+        use gilrs::{Axis, Button};
+        use PhysicalInput::{AxisNegative, AxisPositive};
+
+        let bindings = [
+            (Button::South, Key::Space),
+            (Button::North, Key::Enter),
+            (Button::East, Key::Escape),
+            (Button::RightTrigger, Key::Tab),
+            (Button::DPadUp, Key::UpArrow),
+            (Button::DPadDown, Key::DownArrow),
+            (Button::DPadLeft, Key::LeftArrow),
+            (Button::DPadRight, Key::RightArrow),
+        ]
+        .into_iter()
+        .map(|(button, key)| (PhysicalInput::Button(button), key))
+        .chain(
+            [Axis::LeftStickX, Axis::RightStickX, Axis::DPadX]
+                .into_iter()
+                .flat_map(|axis| {
+                    [
+                        (AxisNegative(axis), Key::LeftArrow),
+                        (AxisPositive(axis), Key::RightArrow),
+                    ]
+                }),
+        )
+        .chain(
+            [Axis::LeftStickY, Axis::RightStickY, Axis::DPadY]
+                .into_iter()
+                .flat_map(|axis| {
+                    [
+                        (AxisNegative(axis), Key::DownArrow),
+                        (AxisPositive(axis), Key::UpArrow),
+                    ]
+                }),
+        )
+        .collect();
+
+        GamepadMapping { bindings }
+    }
+
+    /// A mapping without any binding
+    pub fn empty() -> GamepadMapping {
+        GamepadMapping {
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn key(&self, input: PhysicalInput) -> Option<Key> {
+        self.bindings.get(&input).copied()
+    }
+
+    /// Key sent by a button, bindings of its raw code take precedence
+    pub fn button_key(&self, button: gilrs::Button, code: Code) -> Option<Key> {
+        self.key(PhysicalInput::RawButton(code))
+            .or_else(|| match button {
+                gilrs::Button::Unknown => None,
+                button => self.key(PhysicalInput::Button(button)),
+            })
+    }
+
+    /// Whether any direction of the axis with this raw code is bound
+    pub fn has_raw_axis(&self, code: Code) -> bool {
+        self.key(PhysicalInput::RawAxisPositive(code)).is_some()
+            || self.key(PhysicalInput::RawAxisNegative(code)).is_some()
+    }
+
+    /// Every physical input that sends `key`
+    pub fn inputs(&self, key: Key) -> impl Iterator<Item = PhysicalInput> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound_key)| **bound_key == key)
+            .map(|(input, _)| *input)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (PhysicalInput, Key)> + '_ {
+        self.bindings.iter().map(|(input, key)| (*input, *key))
+    }
+
+    /// Makes `input` the only physical input sending `key`.
+    ///
+    /// Returns the key `input` used to send, if it was a different one.
+    pub fn bind(&mut self, key: Key, input: PhysicalInput) -> Option<Key> {
+        self.bindings.retain(|_, bound_key| *bound_key != key);
+        self.bindings.insert(input, key).filter(|old| *old != key)
+    }
+
+    /// Makes `input` send `key` without removing the other inputs that send `key`.
+    pub fn add_binding(&mut self, key: Key, input: PhysicalInput) -> Option<Key> {
+        self.bindings.insert(input, key).filter(|old| *old != key)
+    }
+
+    pub fn unbind(&mut self, input: PhysicalInput) -> Option<Key> {
+        self.bindings.remove(&input)
+    }
+
+    /// Restores the default bindings of `key`
+    pub fn reset_key(&mut self, key: Key) {
+        self.bindings.retain(|_, bound_key| *bound_key != key);
+        let default = GamepadMapping::default();
+        for input in default.inputs(key) {
+            self.bindings.insert(input, key);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use imgui::Key;
+    use serde::{Deserialize, Serialize};
+
+    use super::{GamepadMapping, PhysicalInput};
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Binding {
+        input: PhysicalInput,
+        #[serde(with = "crate::imgui_serde::key")]
+        key: Key,
+    }
+
+    impl From<Vec<Binding>> for GamepadMapping {
+        fn from(bindings: Vec<Binding>) -> Self {
+            GamepadMapping {
+                bindings: bindings
+                    .into_iter()
+                    .map(|binding| (binding.input, binding.key))
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<GamepadMapping> for Vec<Binding> {
+        fn from(mapping: GamepadMapping) -> Self {
+            let mut bindings: Vec<_> = mapping
+                .bindings
+                .into_iter()
+                .map(|(input, key)| Binding { input, key })
+                .collect();
+            // Keep saved files stable
+            bindings.sort_by_key(|binding| (binding.key as u32, binding.input.to_string()));
+            bindings
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use gilrs::{Button, GamepadId};
+
+use crate::{GamepadEvent, GamepadHandler};
+
+/// Number of player slots, P1 to P4
+pub const PLAYER_SLOTS: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    uuid: [u8; 16],
+    /// `None` while the gamepad is disconnected, the slot stays reserved for it
+    gamepad: Option<GamepadId>,
+}
+
+/// Player slots, remembered by gamepad UUID so that a gamepad that reconnects gets its slot back.
+///
+/// Identical gamepads share a UUID, so they may swap slots when reconnecting.
+#[derive(Debug)]
+pub(crate) struct PlayerSlots {
+    slots: [Option<Slot>; PLAYER_SLOTS],
+    auto_assign: bool,
+    /// Button that takes a free slot, while the join flow runs
+    join_button: Option<Button>,
+    /// Gamepads that joined with a press that isn't released yet, and the button they pressed
+    joining: HashMap<GamepadId, Button>,
+}
+
+impl Default for PlayerSlots {
+    fn default() -> Self {
+        PlayerSlots {
+            slots: [None; PLAYER_SLOTS],
+            auto_assign: true,
+            join_button: None,
+            joining: HashMap::new(),
+        }
+    }
+}
+
+impl PlayerSlots {
+    fn slot(&self, id: GamepadId) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_some_and(|slot| slot.gamepad == Some(id)))
+    }
+
+    fn take_free_slot(&mut self, id: GamepadId, uuid: [u8; 16]) -> Option<usize> {
+        let index = self.slots.iter().position(Option::is_none)?;
+        self.slots[index] = Some(Slot {
+            uuid,
+            gamepad: Some(id),
+        });
+        Some(index)
+    }
+
+    /// Slot reserved for a disconnected gamepad with this UUID
+    fn reserved_slot(&self, uuid: [u8; 16]) -> Option<usize> {
+        // Gamepads without metadata can't be told apart
+        self.slots.iter().position(|slot| {
+            slot.is_some_and(|slot| slot.gamepad.is_none() && slot.uuid == uuid && uuid != [0; 16])
+        })
+    }
+
+    /// Returns the slot the gamepad got, if any
+    pub(crate) fn connect(&mut self, id: GamepadId, uuid: [u8; 16]) -> Option<usize> {
+        if let Some(index) = self.reserved_slot(uuid) {
+            self.slots[index] = Some(Slot {
+                uuid,
+                gamepad: Some(id),
+            });
+            return Some(index);
+        }
+        if self.auto_assign {
+            self.take_free_slot(id, uuid)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn disconnect(&mut self, id: GamepadId) {
+        self.joining.remove(&id);
+        if let Some(index) = self.slot(id) {
+            self.slots[index] = self.slots[index].map(|slot| Slot {
+                gamepad: None,
+                ..slot
+            });
+        }
+    }
+
+    /// The metadata may be registered after the gamepad connected, it then moves to the slot
+    /// reserved for its UUID. Returns the slot it moved to, if any
+    pub(crate) fn update_uuid(&mut self, id: GamepadId, uuid: [u8; 16]) -> Option<usize> {
+        let current = self.slot(id);
+        let index = match self.reserved_slot(uuid) {
+            Some(reserved) => {
+                if let Some(current) = current {
+                    self.slots[current] = None;
+                }
+                reserved
+            }
+            None => current?,
+        };
+        self.slots[index] = Some(Slot {
+            uuid,
+            gamepad: Some(id),
+        });
+        (Some(index) != current).then_some(index)
+    }
+
+    /// Returns `true` if the event belongs to the join flow, and the slot the gamepad got, if any
+    pub(crate) fn join(
+        &mut self,
+        controller_event: &gilrs::Event,
+        uuid: [u8; 16],
+    ) -> (bool, Option<usize>) {
+        use gilrs::EventType as GEvent;
+        let id = controller_event.id;
+        match controller_event.event {
+            GEvent::ButtonPressed(button, _)
+                if Some(button) == self.join_button && self.slot(id).is_none() =>
+            {
+                let slot = self.take_free_slot(id, uuid);
+                if slot.is_some() {
+                    self.joining.insert(id, button);
+                }
+                (slot.is_some(), slot)
+            }
+            GEvent::ButtonReleased(button, _) if self.joining.get(&id) == Some(&button) => {
+                self.joining.remove(&id);
+                (true, None)
+            }
+            GEvent::ButtonChanged(button, _, _) | GEvent::ButtonRepeated(button, _) => {
+                (self.joining.get(&id) == Some(&button), None)
+            }
+            _ => (false, None),
+        }
+    }
+}
+
+impl GamepadHandler {
+    /// Player slot of a connected gamepad, 0 for P1
+    pub fn player_slot(&self, id: GamepadId) -> Option<usize> {
+        self.players.slot(id)
+    }
+
+    /// The connected gamepad in a player slot
+    pub fn player_gamepad(&self, slot: usize) -> Option<GamepadId> {
+        self.players
+            .slots
+            .get(slot)
+            .copied()
+            .flatten()
+            .and_then(|slot| slot.gamepad)
+    }
+
+    /// Moves a connected gamepad to `slot`, swapping it with the gamepad already there.
+    ///
+    /// `None` frees the gamepad's slot.
+    pub fn set_player_slot(&mut self, id: GamepadId, slot: Option<usize>) {
+        if !self.connected_controllers.contains_key(&id) {
+            return;
+        }
+        let uuid = self.gamepad_info(id).uuid;
+        let players = &mut self.players;
+        let old_index = players.slot(id);
+        if let Some(old_index) = old_index {
+            players.slots[old_index] = None;
+        }
+        let Some(index) = slot.filter(|&index| index < PLAYER_SLOTS) else {
+            return;
+        };
+        let displaced = players.slots[index].replace(Slot {
+            uuid,
+            gamepad: Some(id),
+        });
+        if let Some(old_index) = old_index {
+            players.slots[old_index] = displaced;
+        }
+    }
+
+    /// Forgets every slot, including the ones reserved for disconnected gamepads
+    pub fn clear_player_slots(&mut self) {
+        self.players.slots = [None; PLAYER_SLOTS];
+        self.players.joining.clear();
+    }
+
+    /// Whether connecting gamepads take the first free slot, enabled by default
+    pub fn set_auto_assign_players(&mut self, auto_assign: bool) {
+        self.players.auto_assign = auto_assign;
+    }
+
+    /// Starts a "press Start to join" flow: gamepads without a slot take the first free one
+    /// when they press `button`, which doesn't reach imgui.
+    pub fn start_player_join(&mut self, button: Button) {
+        self.players.join_button = Some(button);
+    }
+
+    pub fn stop_player_join(&mut self) {
+        self.players.join_button = None;
+    }
+
+    pub fn is_joining_players(&self) -> bool {
+        self.players.join_button.is_some()
+    }
+
+    pub(crate) fn push_player_joined(&mut self, id: GamepadId, slot: Option<usize>) {
+        if let Some(slot) = slot {
+            let info = self.gamepad_info(id);
+            self.push_event(GamepadEvent::PlayerJoined(info, slot));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GamepadConfig, GamepadInfo};
+
+/// Configuration used by the gamepads matching some criteria.
+///
+/// Criteria are checked in order: UUID, name pattern, and vendor/product IDs.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DeviceProfile {
+    pub name: String,
+    pub uuids: Vec<[u8; 16]>,
+    /// Case insensitive pattern matched against the gamepad name, `*` matches any text
+    pub name_pattern: Option<String>,
+    pub vendor_id: Option<u16>,
+    /// Only checked if `vendor_id` is set. `None` matches every product of the vendor
+    pub product_id: Option<u16>,
+    pub config: GamepadConfig,
+}
+
+impl DeviceProfile {
+    pub fn new(name: impl Into<String>, config: GamepadConfig) -> DeviceProfile {
+        DeviceProfile {
+            name: name.into(),
+            config,
+            ..Default::default()
+        }
+    }
+
+    fn matches_uuid(&self, info: &GamepadInfo) -> bool {
+        self.uuids.contains(&info.uuid)
+    }
+
+    fn matches_name(&self, info: &GamepadInfo) -> bool {
+        match &self.name_pattern {
+            Some(pattern) => {
+                matches_pattern(&pattern.to_lowercase(), &info.name.to_lowercase())
+                    || matches_pattern(&pattern.to_lowercase(), &info.os_name.to_lowercase())
+            }
+            None => false,
+        }
+    }
+
+    fn matches_vendor_product(&self, info: &GamepadInfo) -> bool {
+        match self.vendor_id {
+            Some(vendor_id) => {
+                info.vendor_id == Some(vendor_id)
+                    && (self.product_id.is_none() || self.product_id == info.product_id)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Picks the configuration of each gamepad when it connects
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ProfileRegistry {
+    /// Used by gamepads that don't match any profile
+    pub default: GamepadConfig,
+    pub profiles: Vec<DeviceProfile>,
+}
+
+impl ProfileRegistry {
+    pub fn new(default: GamepadConfig) -> ProfileRegistry {
+        ProfileRegistry {
+            default,
+            profiles: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, profile: DeviceProfile) {
+        self.profiles.push(profile)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DeviceProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut DeviceProfile> {
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+    }
+
+    /// The profile that best matches the gamepad, `None` if the default config should be used
+    pub fn select(&self, info: &GamepadInfo) -> Option<&DeviceProfile> {
+        let criteria: [fn(&DeviceProfile, &GamepadInfo) -> bool; 3] = [
+            DeviceProfile::matches_uuid,
+            DeviceProfile::matches_name,
+            DeviceProfile::matches_vendor_product,
+        ];
+        criteria
+            .into_iter()
+            .find_map(|matches| self.profiles.iter().find(|profile| matches(profile, info)))
+    }
+
+    /// Config of the named profile, or the default one if there's no such profile
+    pub fn config(&self, profile: Option<&str>) -> &GamepadConfig {
+        profile
+            .and_then(|name| self.get(name))
+            .map_or(&self.default, |profile| &profile.config)
+    }
+
+    pub fn config_mut(&mut self, profile: Option<&str>) -> &mut GamepadConfig {
+        let index = profile.and_then(|name| {
+            self.profiles
+                .iter()
+                .position(|profile| profile.name == name)
+        });
+        match index {
+            Some(index) => &mut self.profiles[index].config,
+            None => &mut self.default,
+        }
+    }
+}
+
+/// Glob matching where `*` matches any (possibly empty) text
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_without_wildcard() {
+        assert!(matches_pattern("xbox", "xbox"));
+        assert!(!matches_pattern("xbox", "xbox 360"));
+        assert!(!matches_pattern("xbox", "my xbox"));
+        assert!(matches_pattern("", ""));
+        assert!(!matches_pattern("", "xbox"));
+    }
+
+    #[test]
+    fn pattern_with_wildcards() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("xbox*", "xbox 360 controller"));
+        assert!(matches_pattern("*controller", "xbox 360 controller"));
+        assert!(matches_pattern("*360*", "xbox 360 controller"));
+        assert!(matches_pattern("x*360*er", "xbox 360 controller"));
+        assert!(!matches_pattern("*one*", "xbox 360 controller"));
+        assert!(!matches_pattern("ps*", "xbox 360 controller"));
+    }
+
+    #[test]
+    fn pattern_parts_dont_overlap() {
+        assert!(!matches_pattern("a*a", "a"));
+        assert!(matches_pattern("a*a", "aa"));
+        assert!(!matches_pattern("*ab*b", "ab"));
+        assert!(matches_pattern("*b*b", "bb"));
+        assert!(matches_pattern("a**b", "ab"));
+    }
+}
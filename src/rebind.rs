@@ -0,0 +1,150 @@
+use imgui::{Key, Ui};
+
+use crate::{GamepadHandler, GamepadMapping, PhysicalInput, GAMEPAD_KEYS, MODIFIER_KEYS};
+
+const CONFLICT_POPUP: &str = "Binding conflict";
+const BINDING_COLUMN: f32 = 160.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Conflict {
+    key: Key,
+    input: PhysicalInput,
+    bound_key: Key,
+}
+
+/// Lists every imgui gamepad key, keyboard modifier and other bound key, and lets the player
+/// bind them to a different physical input.
+///
+/// Edits the config of the active gamepad, see [`GamepadHandler::active_config()`].
+///
+/// Must be drawn inside a window, every frame.
+#[derive(Debug, Default)]
+pub struct RebindWidget {
+    /// Key waiting for the next physical input
+    rebinding: Option<Key>,
+    conflict: Option<Conflict>,
+}
+
+impl RebindWidget {
+    pub fn new() -> RebindWidget {
+        RebindWidget::default()
+    }
+
+    pub fn draw(&mut self, ui: &Ui, handler: &mut GamepadHandler) {
+        if let Some(key) = self.rebinding {
+            match handler.take_captured_input() {
+                Some(input) => {
+                    self.rebinding = None;
+                    match handler.active_config().mapping.key(input) {
+                        Some(bound_key) if bound_key != key => {
+                            self.conflict = Some(Conflict {
+                                key,
+                                input,
+                                bound_key,
+                            });
+                            ui.open_popup(CONFLICT_POPUP);
+                        }
+                        _ => {
+                            handler.active_config_mut().mapping.bind(key, input);
+                        }
+                    }
+                }
+                None if !handler.is_capturing() => self.rebinding = None,
+                None => (),
+            }
+        }
+
+        for key in listed_keys(&handler.active_config().mapping) {
+            let _id = ui.push_id(format!("{key:?}"));
+            ui.text(key_label(key));
+            ui.same_line_with_pos(BINDING_COLUMN);
+            let binding = if self.rebinding == Some(key) {
+                String::from("Press a button...")
+            } else {
+                binding_label(&handler.active_config().mapping, key)
+            };
+            if ui.button(format!("{binding}###binding"))
+                && self.rebinding.is_none()
+                && self.conflict.is_none()
+            {
+                self.rebinding = Some(key);
+                handler.capture_next_input();
+            }
+            ui.same_line();
+            if ui.button("Reset") {
+                handler.active_config_mut().mapping.reset_key(key);
+            }
+        }
+
+        ui.separator();
+        if self.rebinding.is_some() {
+            if ui.button("Cancel") {
+                handler.cancel_capture();
+                self.rebinding = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Reset all to default") {
+            handler.active_config_mut().mapping = GamepadMapping::default();
+        }
+
+        ui.modal_popup(CONFLICT_POPUP, || {
+            let Some(conflict) = self.conflict else {
+                ui.close_current_popup();
+                return;
+            };
+            ui.text(format!(
+                "{} is already bound to {}.",
+                conflict.input,
+                key_label(conflict.bound_key)
+            ));
+            if ui.button("Replace") {
+                handler
+                    .active_config_mut()
+                    .mapping
+                    .bind(conflict.key, conflict.input);
+                self.conflict = None;
+                ui.close_current_popup();
+            }
+            ui.same_line();
+            if ui.button("Cancel") {
+                self.conflict = None;
+                ui.close_current_popup();
+            }
+        });
+    }
+}
+
+fn listed_keys(mapping: &GamepadMapping) -> Vec<Key> {
+    let mut keys: Vec<_> = GAMEPAD_KEYS.into_iter().chain(MODIFIER_KEYS).collect();
+    let mut other_keys: Vec<_> = mapping
+        .bindings()
+        .map(|(_, key)| key)
+        .filter(|key| !keys.contains(key))
+        .collect();
+    other_keys.sort_by_key(|key| *key as u32);
+    other_keys.dedup();
+    keys.extend(other_keys);
+    keys
+}
+
+pub(crate) fn key_label(key: Key) -> String {
+    let name = format!("{key:?}");
+    match name.strip_prefix("Gamepad") {
+        Some(name) => name.to_string(),
+        None => match name.strip_prefix("Mod") {
+            Some(modifier) => format!("{modifier} modifier"),
+            None => name,
+        },
+    }
+}
+
+fn binding_label(mapping: &GamepadMapping, key: Key) -> String {
+    let mut inputs: Vec<_> = mapping.inputs(key).map(|input| input.to_string()).collect();
+    if inputs.is_empty() {
+        return String::from("Unbound");
+    }
+
+    inputs.sort();
+    inputs.join(", ")
+}
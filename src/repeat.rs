@@ -0,0 +1,408 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use gilrs::{ev::Code, GamepadId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{debounce::button_event, seconds, GamepadConfig, InputFilter};
+
+/// What triggers the repeats of [`NavRepeat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RepeatSource {
+    /// The handler's own timer
+    #[default]
+    Timer,
+    /// `ButtonRepeated` events, sent by gilrs when its `Repeat` filter is used.
+    ///
+    /// gilrs only repeats buttons, stick directions don't repeat.
+    Gilrs,
+}
+
+/// Key repeat of the D-pad and stick directions, replacing imgui's own.
+///
+/// Each press and repeat reaches imgui as a key held for a single frame, so that imgui never
+/// repeats it with `Io::key_repeat_delay` and `Io::key_repeat_rate`. Both axes of a stick repeat
+/// together, so a held diagonal never passes through another direction. Value changes of a held
+/// stick reach imgui while its key is down, and later repeats use the latest values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NavRepeat {
+    pub source: RepeatSource,
+    /// Seconds before the first repeat
+    pub delay: f32,
+    /// Repeats per second
+    pub rate: f32,
+    /// Repeats per second added for every second the direction is held
+    pub acceleration: f32,
+    pub max_rate: f32,
+}
+
+impl Default for NavRepeat {
+    fn default() -> Self {
+        NavRepeat {
+            source: RepeatSource::Timer,
+            delay: 0.3,
+            rate: 10.0,
+            acceleration: 0.0,
+            max_rate: 30.0,
+        }
+    }
+}
+
+impl NavRepeat {
+    fn interval(&self, held: Duration) -> Duration {
+        let rate = (self.rate + self.acceleration * held.as_secs_f32()).min(self.max_rate);
+        seconds(1.0 / rate.max(f32::EPSILON))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Button(gilrs::Button),
+    /// X and Y axes of a stick or of the D-pad, pressed and released together
+    Stick(gilrs::Axis, gilrs::Axis),
+}
+
+/// X and Y axes of the stick an axis belongs to
+fn stick_axes(axis: gilrs::Axis) -> Option<(gilrs::Axis, gilrs::Axis)> {
+    use gilrs::Axis;
+    let (x, y) = match axis {
+        Axis::LeftStickX | Axis::LeftStickY => (Axis::LeftStickX, Axis::LeftStickY),
+        Axis::RightStickX | Axis::RightStickY => (Axis::RightStickX, Axis::RightStickY),
+        Axis::DPadX | Axis::DPadY => (Axis::DPadX, Axis::DPadY),
+        _ => return None,
+    };
+    Some((x, y))
+}
+
+/// -1, 0 or 1 for each axis of a stick
+fn stick_direction(values: [f32; 2], dead_zone: f32) -> [i8; 2] {
+    values.map(|value| {
+        if value.abs() <= dead_zone {
+            0
+        } else if value > 0.0 {
+            1
+        } else {
+            -1
+        }
+    })
+}
+
+/// When a held direction is pressed in imgui
+#[derive(Debug, Clone, Copy)]
+struct RepeatTimer {
+    pressed_at: SystemTime,
+    /// `None` if the delay is too long to ever repeat
+    next_repeat: Option<SystemTime>,
+    /// Whether the key is down in imgui, until the next update
+    down: bool,
+}
+
+impl RepeatTimer {
+    /// Starts with the key down
+    fn new(repeat: &NavRepeat, time: SystemTime) -> RepeatTimer {
+        RepeatTimer {
+            pressed_at: time,
+            next_repeat: time.checked_add(seconds(repeat.delay)),
+            down: true,
+        }
+    }
+
+    /// Ends the tap of the last frame, or starts a due repeat. Returns the new key state if
+    /// it changed
+    fn update(&mut self, repeat: &NavRepeat, now: SystemTime) -> Option<bool> {
+        if self.down {
+            self.down = false;
+            return Some(false);
+        }
+        let due = self
+            .next_repeat
+            .is_some_and(|next_repeat| now >= next_repeat);
+        if repeat.source != RepeatSource::Timer || !due {
+            return None;
+        }
+        let held_for = now.duration_since(self.pressed_at).unwrap_or_default();
+        self.next_repeat = now.checked_add(repeat.interval(held_for));
+        self.down = true;
+        Some(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HeldDirection {
+    /// Code of the button, or code and value of each axis of the stick
+    inputs: [Option<(Code, f32)>; 2],
+    /// Direction of each axis of the stick while held, unused for buttons
+    stick_direction: [i8; 2],
+    timer: RepeatTimer,
+}
+
+impl HeldDirection {
+    /// Events that press or release the direction in imgui
+    fn events(
+        &self,
+        id: GamepadId,
+        direction: Direction,
+        down: bool,
+        time: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let events = match direction {
+            Direction::Button(button) => {
+                let Some((code, _)) = self.inputs[0] else {
+                    return Vec::new();
+                };
+                vec![button_event(button, code, down)]
+            }
+            Direction::Stick(x_axis, y_axis) => [x_axis, y_axis]
+                .into_iter()
+                .zip(self.inputs)
+                .filter_map(|(axis, input)| {
+                    let (code, value) = input?;
+                    let value = if down { value } else { 0.0 };
+                    Some(gilrs::EventType::AxisChanged(axis, value, code))
+                })
+                .collect(),
+        };
+        events
+            .into_iter()
+            .map(|event| {
+                let mut event = gilrs::Event::new(id, event);
+                event.time = time;
+                event
+            })
+            .collect()
+    }
+}
+
+/// [`crate::FilterStage::NavRepeat`]
+#[derive(Debug, Default)]
+pub(crate) struct RepeatFilter {
+    held: HashMap<(GamepadId, Direction), HeldDirection>,
+    /// Last code and value of each stick axis
+    axes: HashMap<(GamepadId, gilrs::Axis), (Code, f32)>,
+}
+
+fn is_nav_button(button: gilrs::Button) -> bool {
+    matches!(
+        button,
+        gilrs::Button::DPadUp
+            | gilrs::Button::DPadDown
+            | gilrs::Button::DPadLeft
+            | gilrs::Button::DPadRight
+    )
+}
+
+impl RepeatFilter {
+    fn change_axis(
+        &mut self,
+        config: &GamepadConfig,
+        repeat: &NavRepeat,
+        event: gilrs::Event,
+        axis: gilrs::Axis,
+        code: Code,
+        value: f32,
+    ) -> Vec<gilrs::Event> {
+        let Some((x_axis, y_axis)) = stick_axes(axis) else {
+            return vec![event];
+        };
+        let direction = Direction::Stick(x_axis, y_axis);
+        let id = event.id;
+        self.axes.insert((id, axis), (code, value));
+        let inputs = [x_axis, y_axis].map(|axis| self.axes.get(&(id, axis)).copied());
+        let values = inputs.map(|input| input.map_or(0.0, |(_, value)| value));
+        let new_direction = stick_direction(values, config.dead_zone);
+
+        let down = match self.held.get_mut(&(id, direction)) {
+            Some(held) if held.stick_direction == new_direction => {
+                // Still held the same way. Between repeats the stick is at rest in imgui, and
+                // the new value would press it again
+                held.inputs = inputs;
+                return if held.timer.down {
+                    vec![event]
+                } else {
+                    Vec::new()
+                };
+            }
+            Some(held) => held.timer.down,
+            // imgui follows the stick while it isn't held
+            None => true,
+        };
+        if new_direction == [0, 0] {
+            self.held.remove(&(id, direction));
+            return if down { vec![event] } else { Vec::new() };
+        }
+
+        let held = HeldDirection {
+            inputs,
+            stick_direction: new_direction,
+            timer: RepeatTimer::new(repeat, event.time),
+        };
+        self.held.insert((id, direction), held);
+        if down {
+            vec![event]
+        } else {
+            // imgui sees the stick at rest between repeats, both axes must move
+            held.events(id, direction, true, event.time)
+        }
+    }
+}
+
+impl InputFilter for RepeatFilter {
+    fn filter(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event> {
+        use gilrs::EventType as GEvent;
+        let Some(repeat) = config.nav_repeat else {
+            return vec![event];
+        };
+        match event.event {
+            GEvent::ButtonPressed(button, code) if is_nav_button(button) => {
+                let held = HeldDirection {
+                    inputs: [Some((code, 1.0)), None],
+                    stick_direction: [0, 0],
+                    timer: RepeatTimer::new(&repeat, event.time),
+                };
+                self.held
+                    .insert((event.id, Direction::Button(button)), held);
+                vec![event]
+            }
+            GEvent::ButtonReleased(button, _) if is_nav_button(button) => {
+                match self.held.remove(&(event.id, Direction::Button(button))) {
+                    // The tap is over, imgui already saw it released
+                    Some(held) if !held.timer.down => Vec::new(),
+                    _ => vec![event],
+                }
+            }
+            GEvent::ButtonRepeated(button, _) if is_nav_button(button) => {
+                if repeat.source != RepeatSource::Gilrs {
+                    return Vec::new();
+                }
+                let direction = Direction::Button(button);
+                match self.held.get_mut(&(event.id, direction)) {
+                    Some(held) if !held.timer.down => {
+                        held.timer.down = true;
+                        held.events(event.id, direction, true, event.time)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            GEvent::AxisChanged(axis, value, code) => {
+                self.change_axis(config, &repeat, event, axis, code, value)
+            }
+            _ => vec![event],
+        }
+    }
+
+    /// Ends the taps of the last frame and starts the due repeats
+    fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let Some(repeat) = config.nav_repeat else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        for (&(held_id, direction), held) in &mut self.held {
+            if held_id != id {
+                continue;
+            }
+            if let Some(down) = held.timer.update(&repeat, now) {
+                events.extend(held.events(id, direction, down, now));
+            }
+        }
+        events
+    }
+
+    fn reset(&mut self, id: GamepadId) {
+        self.held.retain(|(held_id, _), _| *held_id != id);
+        self.axes.retain(|(axes_id, _), _| *axes_id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1000) + Duration::from_millis(ms)
+    }
+
+    fn repeat(delay: f32, acceleration: f32) -> NavRepeat {
+        NavRepeat {
+            source: RepeatSource::Timer,
+            delay,
+            rate: 4.0,
+            acceleration,
+            max_rate: 16.0,
+        }
+    }
+
+    #[test]
+    fn first_repeat_after_delay() {
+        let repeat = repeat(0.5, 0.0);
+        let mut timer = RepeatTimer::new(&repeat, at(0));
+        assert!(timer.down);
+        assert_eq!(timer.update(&repeat, at(16)), Some(false));
+        assert_eq!(timer.update(&repeat, at(499)), None);
+        assert_eq!(timer.update(&repeat, at(500)), Some(true));
+        assert_eq!(timer.update(&repeat, at(516)), Some(false));
+        assert_eq!(timer.update(&repeat, at(749)), None);
+        assert_eq!(timer.update(&repeat, at(750)), Some(true));
+    }
+
+    #[test]
+    fn rate_ramps_up_to_max_rate() {
+        let repeat = repeat(0.5, 4.0);
+        assert_eq!(repeat.interval(Duration::ZERO), Duration::from_millis(250));
+        assert_eq!(
+            repeat.interval(Duration::from_secs(1)),
+            Duration::from_millis(125)
+        );
+        assert_eq!(
+            repeat.interval(Duration::from_secs(10)),
+            Duration::from_micros(62500)
+        );
+
+        let mut timer = RepeatTimer::new(&repeat, at(0));
+        timer.update(&repeat, at(16));
+        timer.update(&repeat, at(1000));
+        assert_eq!(timer.next_repeat, Some(at(1125)));
+    }
+
+    #[test]
+    fn tap_is_released_on_next_update() {
+        let mut repeat = repeat(0.0, 0.0);
+        let mut timer = RepeatTimer::new(&repeat, at(0));
+        assert_eq!(timer.update(&repeat, at(0)), Some(false));
+        assert!(!timer.down);
+
+        // gilrs' own repeats are only released by the timer
+        repeat.source = RepeatSource::Gilrs;
+        let mut timer = RepeatTimer::new(&repeat, at(0));
+        assert_eq!(timer.update(&repeat, at(16)), Some(false));
+        assert_eq!(timer.update(&repeat, at(1000)), None);
+    }
+
+    #[test]
+    fn long_delay_never_repeats() {
+        for delay in [f32::INFINITY, f32::MAX] {
+            let repeat = repeat(delay, 0.0);
+            let mut timer = RepeatTimer::new(&repeat, at(0));
+            assert_eq!(timer.next_repeat, None);
+            assert_eq!(timer.update(&repeat, at(16)), Some(false));
+            assert_eq!(timer.update(&repeat, at(1_000_000)), None);
+        }
+    }
+
+    #[test]
+    fn stick_direction_of_values() {
+        assert_eq!(stick_direction([0.05, -0.05], 0.1), [0, 0]);
+        assert_eq!(stick_direction([0.7, -0.7], 0.1), [1, -1]);
+        assert_eq!(stick_direction([-0.5, 0.0], 0.1), [-1, 0]);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+
+use gilrs::{GamepadId, Gilrs};
+use imgui::Io;
+
+use crate::{GamepadHandler, GamepadInfo};
+
+#[derive(Debug)]
+struct RouterTarget {
+    handler: GamepadHandler,
+    /// imgui only accepts events for the current context, they wait for the target's update
+    pending: Vec<gilrs::Event>,
+}
+
+/// Sends the events of each gamepad to the imgui context it's routed to, for split-screen UIs.
+///
+/// Every target context has its own [`GamepadHandler`], so key and analogue state, configs and
+/// the `HAS_GAMEPAD` backend flag are kept per context. A gamepad that connects is routed to the
+/// first target without gamepads, the events of gamepads without a route are dropped.
+#[derive(Debug, Default)]
+pub struct GamepadRouter {
+    targets: Vec<RouterTarget>,
+    routes: HashMap<GamepadId, usize>,
+    connected: HashSet<GamepadId>,
+}
+
+impl GamepadRouter {
+    pub fn new() -> GamepadRouter {
+        GamepadRouter::default()
+    }
+
+    /// Adds a target context driven by `handler`, returns its index
+    pub fn add_target(&mut self, handler: GamepadHandler) -> usize {
+        self.targets.push(RouterTarget {
+            handler,
+            pending: Vec::new(),
+        });
+        self.targets.len() - 1
+    }
+
+    pub fn target_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn handler(&self, target: usize) -> &GamepadHandler {
+        &self.targets[target].handler
+    }
+
+    pub fn handler_mut(&mut self, target: usize) -> &mut GamepadHandler {
+        &mut self.targets[target].handler
+    }
+
+    /// The target a gamepad is routed to
+    pub fn route(&self, id: GamepadId) -> Option<usize> {
+        self.routes.get(&id).copied()
+    }
+
+    /// Routes a gamepad to another target, `None` stops routing it.
+    ///
+    /// A connected gamepad disconnects from its old target and connects to the new one.
+    pub fn set_route(&mut self, id: GamepadId, target: Option<usize>) {
+        let target = target.filter(|&target| target < self.targets.len());
+        let old_target = match target {
+            Some(target) => self.routes.insert(id, target),
+            None => self.routes.remove(&id),
+        };
+        if old_target == target || !self.connected.contains(&id) {
+            return;
+        }
+        if let Some(old_target) = old_target {
+            self.targets[old_target]
+                .pending
+                .push(gilrs::Event::new(id, gilrs::EventType::Disconnected));
+        }
+        if let Some(target) = target {
+            self.targets[target]
+                .pending
+                .push(gilrs::Event::new(id, gilrs::EventType::Connected));
+        }
+    }
+
+    /// Queues a gilrs event for the target of its gamepad
+    pub fn handle_event(&mut self, controller_event: &gilrs::Event) {
+        let id = controller_event.id;
+        match controller_event.event {
+            gilrs::EventType::Connected => {
+                self.connected.insert(id);
+                if !self.routes.contains_key(&id) {
+                    let free = (0..self.targets.len())
+                        .find(|target| !self.routes.values().any(|routed| routed == target));
+                    if let Some(target) = free {
+                        self.routes.insert(id, target);
+                    }
+                }
+            }
+            gilrs::EventType::Disconnected => {
+                self.connected.remove(&id);
+            }
+            _ => (),
+        }
+
+        let target = match controller_event.event {
+            // The ID may be given to another gamepad
+            gilrs::EventType::Disconnected => self.routes.remove(&id),
+            _ => self.routes.get(&id).copied(),
+        };
+        if let Some(target) = target {
+            self.targets[target].pending.push(*controller_event);
+        }
+    }
+
+    /// Registers the metadata of every gamepad connected to `gilrs` in every target
+    pub fn update_gamepad_info(&mut self, gilrs: &Gilrs) {
+        for (_, gamepad) in gilrs.gamepads() {
+            self.set_gamepad_info(GamepadInfo::from(gamepad));
+        }
+    }
+
+    pub fn set_gamepad_info(&mut self, info: GamepadInfo) {
+        for target in &mut self.targets {
+            target.handler.set_gamepad_info(info.clone());
+        }
+    }
+
+    /// Sends the queued events of a target to its context, then calls
+    /// [`GamepadHandler::update()`]. Call it once per frame for every target, while its context
+    /// is the current one.
+    pub fn update(&mut self, target: usize, io: &mut Io) {
+        let target = &mut self.targets[target];
+        for controller_event in target.pending.drain(..) {
+            target.handler.handle_gilrs_event(io, &controller_event);
+        }
+        target.handler.update(io);
+    }
+}
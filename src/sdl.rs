@@ -0,0 +1,323 @@
+use std::{env, fmt, fs, io, path::Path};
+
+use gilrs::{Gilrs, GilrsBuilder, MappingSource};
+
+use crate::GamepadInfo;
+
+/// Platform name used in the `platform:` field, as gilrs expects it
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd"))]
+const PLATFORM: &str = "Linux";
+#[cfg(target_os = "macos")]
+const PLATFORM: &str = "Mac OS X";
+#[cfg(target_os = "windows")]
+const PLATFORM: &str = "Windows";
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "windows"
+)))]
+const PLATFORM: &str = "Unknown";
+
+/// Environment variable whose mappings gilrs loads last, overriding the others
+const ENV_MAPPINGS: &str = "SDL_GAMECONTROLLERCONFIG";
+
+/// Fields that don't bind a gamepad element
+const INFO_FIELDS: [&str; 5] = ["platform", "crc", "hint", "sdk>=", "sdk<="];
+
+const ELEMENTS: [&str; 34] = [
+    "a",
+    "b",
+    "x",
+    "y",
+    "back",
+    "guide",
+    "start",
+    "leftstick",
+    "rightstick",
+    "leftshoulder",
+    "rightshoulder",
+    "dpup",
+    "dpdown",
+    "dpleft",
+    "dpright",
+    "leftx",
+    "lefty",
+    "rightx",
+    "righty",
+    "lefttrigger",
+    "righttrigger",
+    "misc1",
+    "misc2",
+    "misc3",
+    "misc4",
+    "misc5",
+    "misc6",
+    "paddle1",
+    "paddle2",
+    "paddle3",
+    "paddle4",
+    "touchpad",
+    "c",
+    "z",
+];
+
+/// A valid line of an SDL `GameControllerDB` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdlMapping {
+    pub uuid: [u8; 16],
+    pub name: String,
+    /// `None` if the mapping applies to every platform
+    pub platform: Option<String>,
+    line: String,
+}
+
+impl SdlMapping {
+    /// Whether gilrs uses the mapping on this platform
+    pub fn is_for_current_platform(&self) -> bool {
+        self.platform
+            .as_deref()
+            .is_none_or(|platform| platform == PLATFORM)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+/// A line that isn't a valid SDL mapping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdlMappingError {
+    /// Starting at 1
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for SdlMappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid SDL mapping on line {}: {}",
+            self.line, self.reason
+        )
+    }
+}
+
+impl std::error::Error for SdlMappingError {}
+
+/// A connected gamepad whose layout is affected by an [`SdlMapping`]
+#[derive(Debug, Clone)]
+pub struct ChangedGamepad {
+    pub gamepad: GamepadInfo,
+    /// Name of the mapping
+    pub mapping: String,
+    /// `false` if gilrs' bundled mapping database or `SDL_GAMECONTROLLERCONFIG` has a mapping
+    /// for the same gamepad, which takes precedence.
+    ///
+    /// gilrs doesn't tell which mapping it picked, so a bundled mapping with the same name as
+    /// the loaded one can't be told apart from it.
+    pub applied: bool,
+}
+
+/// SDL `GameControllerDB` mappings to give gilrs, so unusual controllers get a standard layout.
+///
+/// Comments and blank lines are skipped, invalid lines are reported by
+/// [`SdlMappings::errors()`] and never reach gilrs.
+#[derive(Debug, Clone, Default)]
+pub struct SdlMappings {
+    mappings: Vec<SdlMapping>,
+    errors: Vec<SdlMappingError>,
+}
+
+impl SdlMappings {
+    pub fn parse(s: &str) -> SdlMappings {
+        let mut mappings = SdlMappings::default();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_line(line) {
+                Ok(mapping) => mappings.mappings.push(mapping),
+                Err(reason) => mappings.errors.push(SdlMappingError {
+                    line: index + 1,
+                    reason,
+                }),
+            }
+        }
+        mappings
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<SdlMappings> {
+        Ok(SdlMappings::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn mappings(&self) -> &[SdlMapping] {
+        &self.mappings
+    }
+
+    pub fn errors(&self) -> &[SdlMappingError] {
+        &self.errors
+    }
+
+    /// Adds the valid mappings to a gilrs builder
+    pub fn add_to(&self, builder: GilrsBuilder) -> GilrsBuilder {
+        let mappings: Vec<_> = self.mappings.iter().map(SdlMapping::as_str).collect();
+        builder.add_mappings(&mappings.join("\n"))
+    }
+
+    /// Builds gilrs with the valid mappings, and reports which connected gamepads they changed
+    pub fn build_gilrs(&self) -> Result<(Gilrs, Vec<ChangedGamepad>), Box<gilrs::Error>> {
+        let gilrs = self.add_to(GilrsBuilder::new()).build().map_err(Box::new)?;
+        let changed = self.changed_gamepads(&gilrs);
+        Ok((gilrs, changed))
+    }
+
+    /// Connected gamepads matched by one of the mappings
+    pub fn changed_gamepads(&self, gilrs: &Gilrs) -> Vec<ChangedGamepad> {
+        let env_mappings = env::var(ENV_MAPPINGS)
+            .map(|mappings| SdlMappings::parse(&mappings))
+            .unwrap_or_default();
+        gilrs
+            .gamepads()
+            .filter_map(|(_, gamepad)| {
+                let mapping = self.mappings.iter().rev().find(|mapping| {
+                    mapping.is_for_current_platform() && mapping.uuid == gamepad.uuid()
+                })?;
+                let overridden = env_mappings.mappings.iter().any(|env_mapping| {
+                    env_mapping.is_for_current_platform()
+                        && env_mapping.uuid == mapping.uuid
+                        && env_mapping.line != mapping.line
+                });
+                let applied = gamepad.mapping_source() == MappingSource::SdlMappings
+                    && gamepad.map_name() == Some(mapping.name.as_str())
+                    && !overridden;
+                Some(ChangedGamepad {
+                    gamepad: GamepadInfo::from(gamepad),
+                    mapping: mapping.name.clone(),
+                    applied,
+                })
+            })
+            .collect()
+    }
+}
+
+fn parse_line(line: &str) -> Result<SdlMapping, String> {
+    let mut fields = line.split(',');
+    let guid = fields.next().unwrap_or_default();
+    let uuid = parse_guid(guid).ok_or_else(|| format!("`{guid}` isn't a 32 digit GUID"))?;
+    let name = fields.next().unwrap_or_default().trim();
+    if name.is_empty() {
+        return Err(String::from("missing controller name"));
+    }
+
+    let mut platform = None;
+    for field in fields.map(str::trim).filter(|field| !field.is_empty()) {
+        let Some((key, value)) = field.split_once(':') else {
+            return Err(format!("`{field}` isn't a `key:value` pair"));
+        };
+        if key == "platform" {
+            platform = Some(value.to_string());
+        }
+        if INFO_FIELDS.contains(&key) {
+            continue;
+        }
+
+        let element = key.trim_start_matches(['+', '-']);
+        if !ELEMENTS.contains(&element) {
+            return Err(format!("unknown element `{key}`"));
+        }
+        if !is_valid_binding(value) {
+            return Err(format!("invalid binding `{value}` for `{key}`"));
+        }
+    }
+
+    Ok(SdlMapping {
+        uuid,
+        name: name.to_string(),
+        platform,
+        line: line.to_string(),
+    })
+}
+
+fn parse_guid(guid: &str) -> Option<[u8; 16]> {
+    if guid.len() != 32 {
+        return None;
+    }
+    let mut uuid = [0; 16];
+    for (index, byte) in uuid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(guid.get(index * 2..index * 2 + 2)?, 16).ok()?;
+    }
+    Some(uuid)
+}
+
+/// `b0`, `h0.4`, or `a0` with an optional `+`/`-` range and `~` inversion
+fn is_valid_binding(value: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if let Some(index) = value.strip_prefix('b') {
+        return is_number(index);
+    }
+    if let Some(hat) = value.strip_prefix('h') {
+        return hat
+            .split_once('.')
+            .is_some_and(|(index, mask)| is_number(index) && is_number(mask));
+    }
+    let axis = value.trim_start_matches(['+', '-']);
+    let axis = axis.strip_suffix('~').unwrap_or(axis);
+    axis.strip_prefix('a').is_some_and(is_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XBOX: &str = "030000005e0400008e02000014010000,Xbox 360 Controller,a:b0,b:b1,\
+        leftx:a0,lefty:a1~,dpup:h0.1,+righttrigger:+a5,platform:Linux,";
+
+    #[test]
+    fn parse_valid_line() {
+        let mapping = parse_line(XBOX).unwrap();
+        assert_eq!(mapping.name, "Xbox 360 Controller");
+        assert_eq!(mapping.platform.as_deref(), Some("Linux"));
+        assert_eq!(mapping.uuid[..4], [0x03, 0x00, 0x00, 0x00]);
+        assert_eq!(mapping.as_str(), XBOX);
+    }
+
+    #[test]
+    fn parse_invalid_lines() {
+        assert!(parse_line("0300,Pad,a:b0").is_err());
+        assert!(parse_line("030000005e0400008e02000014010000,,a:b0").is_err());
+        assert!(parse_line("030000005e0400008e02000014010000,Pad,a").is_err());
+        assert!(parse_line("030000005e0400008e02000014010000,Pad,jump:b0").is_err());
+        assert!(parse_line("030000005e0400008e02000014010000,Pad,a:x0").is_err());
+    }
+
+    #[test]
+    fn parse_reports_line_numbers() {
+        let mappings = SdlMappings::parse(&format!("# comment\n\n{XBOX}\nbroken\n"));
+        assert_eq!(mappings.mappings().len(), 1);
+        assert_eq!(mappings.errors().len(), 1);
+        assert_eq!(mappings.errors()[0].line, 4);
+    }
+
+    #[test]
+    fn guid() {
+        let uuid = parse_guid("000102030405060708090a0b0c0d0eFF").unwrap();
+        assert_eq!(uuid[..3], [0, 1, 2]);
+        assert_eq!(uuid[15], 0xff);
+        assert_eq!(parse_guid("0001"), None);
+        assert_eq!(parse_guid("zz0102030405060708090a0b0c0d0e0f"), None);
+        assert_eq!(parse_guid("é0102030405060708090a0b0c0d0e0f"), None);
+    }
+
+    #[test]
+    fn bindings() {
+        for valid in ["b0", "b12", "h0.4", "a3", "+a2", "-a2", "a1~", "-a1~"] {
+            assert!(is_valid_binding(valid), "{valid}");
+        }
+        for invalid in ["", "b", "bx", "h0", "h.1", "a", "a~", "c0", "b1~"] {
+            assert!(!is_valid_binding(invalid), "{invalid}");
+        }
+    }
+}
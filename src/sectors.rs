@@ -0,0 +1,171 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How stick positions become nav directions
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StickDirections {
+    /// X and Y press their directions independently, a diagonal push presses two of them
+    #[default]
+    Independent,
+    /// The stick presses up, down, left or right
+    FourWay(StickSectors),
+    /// The stick presses one direction, or the two directions of a single diagonal
+    EightWay(StickSectors),
+}
+
+/// Angular sectors of a stick, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StickSectors {
+    /// Width of the up, down, left and right sectors.
+    ///
+    /// The diagonal sectors of [`StickDirections::EightWay`] fill the rest. With
+    /// [`StickDirections::FourWay`], widths below 90 leave gaps that press nothing.
+    pub cardinal_size: f32,
+    /// How far past the edge of its sector the stick must go to leave it
+    pub hysteresis: f32,
+}
+
+impl StickDirections {
+    pub fn four_way() -> StickDirections {
+        StickDirections::FourWay(StickSectors {
+            cardinal_size: 90.0,
+            hysteresis: 10.0,
+        })
+    }
+
+    pub fn eight_way() -> StickDirections {
+        StickDirections::EightWay(StickSectors {
+            cardinal_size: 45.0,
+            hysteresis: 10.0,
+        })
+    }
+
+    /// Sector of the stick position, counter-clockwise from 0 (right) to 7 (down-right).
+    ///
+    /// `current` is the sector the stick is in, it's kept within the hysteresis.
+    pub(crate) fn sector(self, current: Option<u8>, x: f32, y: f32, dead_zone: f32) -> Option<u8> {
+        let (sectors, eight_way) = match self {
+            StickDirections::Independent => return None,
+            StickDirections::FourWay(sectors) => (sectors, false),
+            StickDirections::EightWay(sectors) => (sectors, true),
+        };
+        if x.hypot(y) <= dead_zone {
+            return None;
+        }
+
+        let angle = y.atan2(x).to_degrees();
+        let half_size = |sector: u8| {
+            if sector.is_multiple_of(2) {
+                sectors.cardinal_size / 2.0
+            } else if eight_way {
+                (90.0 - sectors.cardinal_size) / 2.0
+            } else {
+                -1.0
+            }
+        };
+        let contains = |sector: u8, margin: f32| {
+            let distance = (angle - f32::from(sector) * 45.0).rem_euclid(360.0);
+            distance.min(360.0 - distance) <= half_size(sector) + margin
+        };
+
+        if let Some(current) = current.filter(|&current| contains(current, sectors.hysteresis)) {
+            return Some(current);
+        }
+        (0..8).find(|&sector| contains(sector, 0.0))
+    }
+}
+
+/// X and Y directions pressed in a sector, -1, 0 or 1
+pub(crate) fn sector_direction(sector: Option<u8>) -> (i8, i8) {
+    match sector {
+        Some(0) => (1, 0),
+        Some(1) => (1, 1),
+        Some(2) => (0, 1),
+        Some(3) => (-1, 1),
+        Some(4) => (-1, 0),
+        Some(5) => (-1, -1),
+        Some(6) => (0, -1),
+        Some(7) => (1, -1),
+        _ => (0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(angle: f32) -> (f32, f32) {
+        let angle = angle.to_radians();
+        (angle.cos(), angle.sin())
+    }
+
+    fn sector(directions: StickDirections, current: Option<u8>, angle: f32) -> Option<u8> {
+        let (x, y) = at(angle);
+        directions.sector(current, x, y, 0.1)
+    }
+
+    #[test]
+    fn independent_has_no_sectors() {
+        assert_eq!(sector(StickDirections::Independent, None, 0.0), None);
+    }
+
+    #[test]
+    fn dead_zone() {
+        let directions = StickDirections::eight_way();
+        assert_eq!(directions.sector(None, 0.05, 0.05, 0.1), None);
+        assert_eq!(directions.sector(Some(1), 0.05, 0.05, 0.1), None);
+    }
+
+    #[test]
+    fn four_way() {
+        let directions = StickDirections::four_way();
+        assert_eq!(sector(directions, None, 0.0), Some(0));
+        assert_eq!(sector(directions, None, 44.0), Some(0));
+        assert_eq!(sector(directions, None, 46.0), Some(2));
+        assert_eq!(sector(directions, None, 180.0), Some(4));
+        assert_eq!(sector(directions, None, -90.0), Some(6));
+        assert_eq!(sector(directions, None, -30.0), Some(0));
+    }
+
+    #[test]
+    fn four_way_gaps() {
+        let directions = StickDirections::FourWay(StickSectors {
+            cardinal_size: 60.0,
+            hysteresis: 0.0,
+        });
+        assert_eq!(sector(directions, None, 20.0), Some(0));
+        assert_eq!(sector(directions, None, 45.0), None);
+    }
+
+    #[test]
+    fn eight_way() {
+        let directions = StickDirections::eight_way();
+        assert_eq!(sector(directions, None, 0.0), Some(0));
+        assert_eq!(sector(directions, None, 45.0), Some(1));
+        assert_eq!(sector(directions, None, 135.0), Some(3));
+        assert_eq!(sector(directions, None, -45.0), Some(7));
+        assert_eq!(sector(directions, None, 200.0), Some(4));
+    }
+
+    #[test]
+    fn hysteresis_keeps_the_current_sector() {
+        let directions = StickDirections::eight_way();
+        // Right's sector ends at 22.5 degrees, leaving it needs 10 more
+        assert_eq!(sector(directions, Some(0), 30.0), Some(0));
+        assert_eq!(sector(directions, Some(0), 35.0), Some(1));
+        assert_eq!(sector(directions, None, 30.0), Some(1));
+        // Across the -180/180 wrap
+        assert_eq!(sector(directions, Some(4), -160.0), Some(4));
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(sector_direction(None), (0, 0));
+        assert_eq!(sector_direction(Some(0)), (1, 0));
+        assert_eq!(sector_direction(Some(3)), (-1, 1));
+        assert_eq!(sector_direction(Some(6)), (0, -1));
+        assert_eq!(sector_direction(Some(8)), (0, 0));
+    }
+}
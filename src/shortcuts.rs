@@ -0,0 +1,33 @@
+use imgui::Key;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::GamepadConfig;
+
+/// Makes a button send a keyboard shortcut, such as Ctrl+Z, instead of its mapped key.
+///
+/// The keys are pressed in order when the button is pressed, and released in reverse order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Shortcut {
+    pub button: gilrs::Button,
+    /// Modifiers such as `Key::ModCtrl` followed by the key, e.g. `[Key::ModCtrl, Key::Z]`
+    #[cfg_attr(feature = "serde", serde(with = "crate::imgui_serde::keys"))]
+    pub keys: Vec<Key>,
+}
+
+impl Shortcut {
+    pub fn new(button: gilrs::Button, keys: impl Into<Vec<Key>>) -> Shortcut {
+        Shortcut {
+            button,
+            keys: keys.into(),
+        }
+    }
+}
+
+pub(crate) fn find_shortcut(config: &GamepadConfig, button: gilrs::Button) -> Option<&Shortcut> {
+    config
+        .shortcuts
+        .iter()
+        .find(|shortcut| shortcut.button == button)
+}
@@ -0,0 +1,245 @@
+use std::{collections::HashMap, f32::consts::PI, time::SystemTime};
+
+use gilrs::{ev::Code, GamepadId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GamepadConfig, InputFilter};
+
+/// Output closer than this to the input is snapped to it
+const SETTLED: f32 = 1e-3;
+/// Used when events arrive at the same time, in seconds
+const MIN_STEP: f32 = 1e-3;
+
+/// Filter that removes jitter from noisy stick axes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Smoothing {
+    #[default]
+    None,
+    /// Exponential moving average, `alpha` is the weight of each new value, from 0 to 1
+    Ema { alpha: f32 },
+    /// One Euro filter: smooths a lot when the stick moves slowly, and little when it moves fast
+    OneEuro {
+        /// Cutoff frequency in Hz at low speeds, lower values remove more jitter
+        min_cutoff: f32,
+        /// How much the cutoff frequency grows with speed, higher values reduce lag
+        beta: f32,
+        /// Cutoff frequency in Hz used to estimate the speed
+        derivative_cutoff: f32,
+    },
+}
+
+impl Smoothing {
+    pub fn one_euro() -> Smoothing {
+        Smoothing::OneEuro {
+            min_cutoff: 1.0,
+            beta: 0.5,
+            derivative_cutoff: 1.0,
+        }
+    }
+}
+
+/// [`Smoothing`] of each stick
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StickSmoothing {
+    pub left: Smoothing,
+    pub right: Smoothing,
+}
+
+impl StickSmoothing {
+    /// Smoothing of a stick axis, `None` for other axes
+    fn axis(&self, axis: gilrs::Axis) -> Option<Smoothing> {
+        match axis {
+            gilrs::Axis::LeftStickX | gilrs::Axis::LeftStickY => Some(self.left),
+            gilrs::Axis::RightStickX | gilrs::Axis::RightStickY => Some(self.right),
+            _ => None,
+        }
+    }
+}
+
+/// Filter state of an axis
+#[derive(Debug, Clone, Copy)]
+struct AxisFilter {
+    /// Last unfiltered value
+    raw: f32,
+    value: f32,
+    derivative: f32,
+    time: SystemTime,
+}
+
+impl AxisFilter {
+    /// Starts at rest
+    fn new(time: SystemTime) -> AxisFilter {
+        AxisFilter {
+            raw: 0.0,
+            value: 0.0,
+            derivative: 0.0,
+            time,
+        }
+    }
+
+    /// gilrs sends no events while the value doesn't change, the filter must be fed until
+    /// its output reaches the last value
+    fn is_settled(&self) -> bool {
+        self.value == self.raw
+    }
+
+    fn filter(&mut self, smoothing: Smoothing, raw: f32, time: SystemTime) -> f32 {
+        let step = time
+            .duration_since(self.time)
+            .map_or(0.0, |step| step.as_secs_f32())
+            .max(MIN_STEP);
+        self.raw = raw;
+        self.time = time;
+        let value = match smoothing {
+            Smoothing::None => raw,
+            Smoothing::Ema { alpha } => self.value + alpha.clamp(0.0, 1.0) * (raw - self.value),
+            Smoothing::OneEuro {
+                min_cutoff,
+                beta,
+                derivative_cutoff,
+            } => {
+                let derivative = (raw - self.value) / step;
+                self.derivative +=
+                    smoothing_factor(derivative_cutoff, step) * (derivative - self.derivative);
+                let cutoff = min_cutoff + beta * self.derivative.abs();
+                self.value + smoothing_factor(cutoff, step) * (raw - self.value)
+            }
+        };
+        self.value = if (value - raw).abs() < SETTLED {
+            raw
+        } else {
+            value
+        };
+        self.value
+    }
+}
+
+fn smoothing_factor(cutoff: f32, step: f32) -> f32 {
+    let time_constant = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + time_constant / step)
+}
+
+/// [`crate::FilterStage::Smoothing`]
+#[derive(Debug, Default)]
+pub(crate) struct SmoothingFilter {
+    /// Filter of each axis, and the code of its last event
+    axes: HashMap<(GamepadId, gilrs::Axis), (Code, AxisFilter)>,
+}
+
+impl InputFilter for SmoothingFilter {
+    fn filter(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event> {
+        let gilrs::EventType::AxisChanged(axis, value, code) = event.event else {
+            return vec![event];
+        };
+        let smoothing = match config.smoothing.axis(axis) {
+            Some(Smoothing::None) | None => return vec![event],
+            Some(smoothing) => smoothing,
+        };
+        let (last_code, filter) = self
+            .axes
+            .entry((event.id, axis))
+            .or_insert_with(|| (code, AxisFilter::new(event.time)));
+        *last_code = code;
+        let value = filter.filter(smoothing, value, event.time);
+        let mut event = event;
+        event.event = gilrs::EventType::AxisChanged(axis, value, code);
+        vec![event]
+    }
+
+    /// gilrs sends no event while the axis doesn't move, the filter is fed its last value
+    /// until it settles
+    fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let mut events = Vec::new();
+        for (&(filter_id, axis), (code, filter)) in &mut self.axes {
+            if filter_id != id || filter.is_settled() {
+                continue;
+            }
+            let smoothing = config.smoothing.axis(axis).unwrap_or_default();
+            let value = filter.filter(smoothing, filter.raw, now);
+            let mut event =
+                gilrs::Event::new(id, gilrs::EventType::AxisChanged(axis, value, *code));
+            event.time = now;
+            events.push(event);
+        }
+        events
+    }
+
+    fn reset(&mut self, id: GamepadId) {
+        self.axes.retain(|(filter_id, _), _| *filter_id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Feeds `raw` every 10 ms, like `update()` every frame, and returns the outputs
+    fn settle(smoothing: Smoothing, raw: f32, steps: usize) -> Vec<f32> {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut filter = AxisFilter::new(start);
+        (1..=steps)
+            .map(|step| {
+                let time = start + Duration::from_millis(10 * step as u64);
+                filter.filter(smoothing, raw, time)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn none_passes_values_through() {
+        assert_eq!(settle(Smoothing::None, 0.7, 1), [0.7]);
+    }
+
+    #[test]
+    fn ema() {
+        let values = settle(Smoothing::Ema { alpha: 0.5 }, 1.0, 3);
+        assert_eq!(values, [0.5, 0.75, 0.875]);
+    }
+
+    #[test]
+    fn ema_settles() {
+        let values = settle(Smoothing::Ema { alpha: 0.3 }, 1.0, 100);
+        assert_eq!(values.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn one_euro_moves_monotonically_and_settles() {
+        let values = settle(Smoothing::one_euro(), 1.0, 500);
+        assert!(values[0] > 0.0 && values[0] < 1.0);
+        assert!(values.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(values.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn one_euro_follows_fast_moves_closer() {
+        let slow = Smoothing::OneEuro {
+            min_cutoff: 1.0,
+            beta: 0.0,
+            derivative_cutoff: 1.0,
+        };
+        let fast = Smoothing::OneEuro {
+            min_cutoff: 1.0,
+            beta: 5.0,
+            derivative_cutoff: 1.0,
+        };
+        assert!(settle(fast, 1.0, 5)[4] > settle(slow, 1.0, 5)[4]);
+    }
+
+    #[test]
+    fn events_at_the_same_time() {
+        let mut filter = AxisFilter::new(SystemTime::UNIX_EPOCH);
+        let value = filter.filter(Smoothing::one_euro(), 1.0, SystemTime::UNIX_EPOCH);
+        assert!(value.is_finite());
+    }
+}
@@ -0,0 +1,248 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GamepadConfig, InputFilter};
+
+/// Maps how far an axis is pushed to the value the handler uses, keeping its sign
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Raises the value to this power, above 1 gives finer control near the center.
+    ///
+    /// Exponents that aren't positive are treated as 1.
+    Exponential(f32),
+    /// `[input, output]` points between 0 and 1, sorted by input and joined by straight lines.
+    ///
+    /// The curve starts at `[0, 0]` and ends at `[1, 1]` unless the points say otherwise.
+    Points(Vec<[f32; 2]>),
+}
+
+impl ResponseCurve {
+    fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs().min(1.0);
+        let magnitude = match self {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Exponential(exponent) if *exponent > 0.0 => magnitude.powf(*exponent),
+            ResponseCurve::Exponential(_) => magnitude,
+            ResponseCurve::Points(points) => interpolate(points, magnitude),
+        };
+        magnitude.copysign(value)
+    }
+}
+
+fn interpolate(points: &[[f32; 2]], input: f32) -> f32 {
+    let mut previous = [0.0, 0.0];
+    for &point in points.iter().chain(&[[1.0, 1.0]]) {
+        if input <= point[0] {
+            let width = point[0] - previous[0];
+            if width <= 0.0 {
+                return point[1];
+            }
+            let t = (input - previous[0]) / width;
+            return previous[1] + t * (point[1] - previous[1]);
+        }
+        previous = point;
+    }
+    previous[1]
+}
+
+/// Adjustments applied to an axis before the handler stores its value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AxisTuning {
+    /// Sticks send the opposite direction, triggers rest at fully pressed
+    pub invert: bool,
+    /// Multiplies the value before the curve, so the full range is reached with a shorter push
+    pub sensitivity: f32,
+    pub curve: ResponseCurve,
+    /// Multiplies the value after the curve
+    pub scale: f32,
+}
+
+impl Default for AxisTuning {
+    fn default() -> Self {
+        AxisTuning {
+            invert: false,
+            sensitivity: 1.0,
+            curve: ResponseCurve::Linear,
+            scale: 1.0,
+        }
+    }
+}
+
+impl AxisTuning {
+    /// `value` goes from -1 to 1
+    pub fn apply_stick(&self, value: f32) -> f32 {
+        let value = if self.invert { -value } else { value };
+        self.shape(value).clamp(-1.0, 1.0)
+    }
+
+    /// `value` goes from 0 to 1
+    pub fn apply_trigger(&self, value: f32) -> f32 {
+        let value = if self.invert { 1.0 - value } else { value };
+        self.shape(value).clamp(0.0, 1.0)
+    }
+
+    fn shape(&self, value: f32) -> f32 {
+        let value = (value * self.sensitivity).clamp(-1.0, 1.0);
+        self.curve.apply(value) * self.scale
+    }
+}
+
+/// [`AxisTuning`] of each stick axis and trigger
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InputTuning {
+    pub left_stick_x: AxisTuning,
+    pub left_stick_y: AxisTuning,
+    pub right_stick_x: AxisTuning,
+    pub right_stick_y: AxisTuning,
+    /// Applies to L2, whether it's reported as a button or as `Axis::LeftZ`
+    pub left_trigger: AxisTuning,
+    /// Applies to R2, whether it's reported as a button or as `Axis::RightZ`
+    pub right_trigger: AxisTuning,
+}
+
+impl InputTuning {
+    /// Tuning of a stick axis, `None` for other axes
+    fn stick(&self, axis: gilrs::Axis) -> Option<&AxisTuning> {
+        match axis {
+            gilrs::Axis::LeftStickX => Some(&self.left_stick_x),
+            gilrs::Axis::LeftStickY => Some(&self.left_stick_y),
+            gilrs::Axis::RightStickX => Some(&self.right_stick_x),
+            gilrs::Axis::RightStickY => Some(&self.right_stick_y),
+            _ => None,
+        }
+    }
+}
+
+/// [`crate::FilterStage::Tuning`]
+#[derive(Debug, Default)]
+pub(crate) struct TuningFilter;
+
+impl InputFilter for TuningFilter {
+    fn filter(&mut self, config: &GamepadConfig, mut event: gilrs::Event) -> Vec<gilrs::Event> {
+        use gilrs::{Axis, Button, EventType as GEvent};
+        let tuning = &config.tuning;
+        let range = config.trigger_axis_range;
+        event.event = match event.event {
+            GEvent::ButtonChanged(button @ Button::LeftTrigger2, value, code) => {
+                GEvent::ButtonChanged(button, tuning.left_trigger.apply_trigger(value), code)
+            }
+            GEvent::ButtonChanged(button @ Button::RightTrigger2, value, code) => {
+                GEvent::ButtonChanged(button, tuning.right_trigger.apply_trigger(value), code)
+            }
+            GEvent::AxisChanged(axis @ (Axis::LeftZ | Axis::RightZ), value, code) => {
+                let trigger = match axis {
+                    Axis::LeftZ => &tuning.left_trigger,
+                    _ => &tuning.right_trigger,
+                };
+                let value = trigger.apply_trigger(range.normalize(value));
+                GEvent::AxisChanged(axis, range.denormalize(value), code)
+            }
+            GEvent::AxisChanged(axis, value, code) => match tuning.stick(axis) {
+                Some(stick) => GEvent::AxisChanged(axis, stick.apply_stick(value), code),
+                None => event.event,
+            },
+            other => other,
+        };
+        vec![event]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{actual} isn't close to {expected}"
+        );
+    }
+
+    #[test]
+    fn linear() {
+        assert_close(ResponseCurve::Linear.apply(0.3), 0.3);
+        assert_close(ResponseCurve::Linear.apply(-0.3), -0.3);
+        assert_close(ResponseCurve::Linear.apply(1.5), 1.0);
+    }
+
+    #[test]
+    fn exponential_keeps_the_sign() {
+        let curve = ResponseCurve::Exponential(2.0);
+        assert_close(curve.apply(0.5), 0.25);
+        assert_close(curve.apply(-0.5), -0.25);
+        assert_close(curve.apply(0.0), 0.0);
+        assert_close(curve.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn exponential_without_positive_exponent_is_linear() {
+        for exponent in [0.0, -2.0, f32::NAN] {
+            let curve = ResponseCurve::Exponential(exponent);
+            assert_close(curve.apply(0.0), 0.0);
+            assert_close(curve.apply(0.5), 0.5);
+            assert_close(curve.apply(-1.0), -1.0);
+        }
+    }
+
+    #[test]
+    fn interpolation() {
+        let points = [[0.5, 0.2]];
+        assert_close(interpolate(&points, 0.0), 0.0);
+        assert_close(interpolate(&points, 0.25), 0.1);
+        assert_close(interpolate(&points, 0.5), 0.2);
+        assert_close(interpolate(&points, 0.75), 0.6);
+        assert_close(interpolate(&points, 1.0), 1.0);
+        assert_close(interpolate(&[], 0.4), 0.4);
+    }
+
+    #[test]
+    fn interpolation_of_explicit_ends() {
+        // A dead zone up to 0.1 and full output from 0.9
+        let points = [[0.1, 0.0], [0.9, 1.0]];
+        assert_close(interpolate(&points, 0.05), 0.0);
+        assert_close(interpolate(&points, 0.5), 0.5);
+        assert_close(interpolate(&points, 0.95), 1.0);
+        // Vertical steps don't divide by zero
+        assert_close(interpolate(&[[0.5, 0.2], [0.5, 0.8]], 0.5), 0.2);
+    }
+
+    #[test]
+    fn points_curve_keeps_the_sign() {
+        let curve = ResponseCurve::Points(vec![[0.5, 0.2]]);
+        assert_close(curve.apply(-0.25), -0.1);
+    }
+
+    #[test]
+    fn stick_tuning() {
+        let tuning = AxisTuning {
+            invert: true,
+            sensitivity: 2.0,
+            curve: ResponseCurve::Exponential(2.0),
+            scale: 0.5,
+        };
+        assert_close(tuning.apply_stick(0.25), -0.125);
+        assert_close(tuning.apply_stick(-1.0), 0.5);
+    }
+
+    #[test]
+    fn trigger_tuning() {
+        let inverted = AxisTuning {
+            invert: true,
+            ..Default::default()
+        };
+        assert_close(inverted.apply_trigger(0.0), 1.0);
+        assert_close(inverted.apply_trigger(1.0), 0.0);
+        let scaled = AxisTuning {
+            scale: 3.0,
+            ..Default::default()
+        };
+        assert_close(scaled.apply_trigger(0.5), 1.0);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use gilrs::{ev::Code, GamepadId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{debounce::button_event, GamepadConfig, InputFilter};
+
+/// Auto-fire: buttons held down keep pressing and releasing their imgui key
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Turbo {
+    pub buttons: Vec<gilrs::Button>,
+    /// Presses per second, at most half the frame rate
+    pub rate: f32,
+}
+
+impl Default for Turbo {
+    fn default() -> Self {
+        Turbo {
+            buttons: Vec::new(),
+            rate: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HeldButton {
+    /// State sent to the next filters
+    reported: bool,
+    next_toggle: SystemTime,
+}
+
+impl HeldButton {
+    fn new(time: SystemTime, half_period: Duration) -> HeldButton {
+        HeldButton {
+            reported: true,
+            next_toggle: time + half_period,
+        }
+    }
+
+    /// Whether the button was released or pressed again by turbo
+    fn toggle(&mut self, now: SystemTime, half_period: Duration) -> bool {
+        if now < self.next_toggle {
+            return false;
+        }
+        self.reported = !self.reported;
+        self.next_toggle = now + half_period;
+        true
+    }
+}
+
+/// [`crate::FilterStage::Turbo`]
+#[derive(Debug, Default)]
+pub(crate) struct TurboFilter {
+    /// Held buttons, and the code of their press
+    held: HashMap<(GamepadId, gilrs::Button), (Code, HeldButton)>,
+}
+
+fn half_period(turbo: &Turbo) -> Duration {
+    Duration::from_secs_f32(0.5 / turbo.rate.max(f32::EPSILON))
+}
+
+impl InputFilter for TurboFilter {
+    fn filter(&mut self, config: &GamepadConfig, event: gilrs::Event) -> Vec<gilrs::Event> {
+        match event.event {
+            gilrs::EventType::ButtonPressed(button, code)
+                if config.turbo.buttons.contains(&button) =>
+            {
+                let held = HeldButton::new(event.time, half_period(&config.turbo));
+                self.held.insert((event.id, button), (code, held));
+                vec![event]
+            }
+            gilrs::EventType::ButtonReleased(button, _) => {
+                match self.held.remove(&(event.id, button)) {
+                    // Turbo already released it
+                    Some((_, held)) if !held.reported => Vec::new(),
+                    _ => vec![event],
+                }
+            }
+            _ => vec![event],
+        }
+    }
+
+    fn update(
+        &mut self,
+        id: GamepadId,
+        config: &GamepadConfig,
+        now: SystemTime,
+    ) -> Vec<gilrs::Event> {
+        let mut events = Vec::new();
+        for (&(held_id, button), (code, held)) in &mut self.held {
+            if held_id != id || !held.toggle(now, half_period(&config.turbo)) {
+                continue;
+            }
+            let mut event = gilrs::Event::new(id, button_event(button, *code, held.reported));
+            event.time = now;
+            events.push(event);
+        }
+        events
+    }
+
+    fn reset(&mut self, id: GamepadId) {
+        self.held.retain(|(held_id, _), _| *held_id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn half_period_of_rate() {
+        let turbo = Turbo {
+            rate: 4.0,
+            ..Default::default()
+        };
+        assert_eq!(half_period(&turbo), Duration::from_millis(125));
+        for rate in [0.0, -1.0, f32::NAN] {
+            let turbo = Turbo {
+                rate,
+                ..Default::default()
+            };
+            assert!(half_period(&turbo) > Duration::from_secs(3600));
+        }
+    }
+
+    #[test]
+    fn toggles_every_half_period() {
+        let half_period = Duration::from_millis(50);
+        let mut held = HeldButton::new(at(0), half_period);
+        assert!(held.reported);
+        assert!(!held.toggle(at(49), half_period));
+        assert!(held.toggle(at(50), half_period));
+        assert!(!held.reported);
+        assert!(!held.toggle(at(90), half_period));
+        assert!(held.toggle(at(100), half_period));
+        assert!(held.reported);
+    }
+
+    #[test]
+    fn late_updates_toggle_once() {
+        let half_period = Duration::from_millis(50);
+        let mut held = HeldButton::new(at(0), half_period);
+        assert!(held.toggle(at(500), half_period));
+        assert!(!held.toggle(at(520), half_period));
+        assert!(held.toggle(at(550), half_period));
+    }
+}